@@ -9,7 +9,7 @@ import prettierConfig from 'eslint-config-prettier';
 
 export default [
   {
-    ignores: ['dist', 'node_modules', 'src-tauri'],
+    ignores: ['dist', 'node_modules', 'src-tauri', 'src/bindings'],
   },
   {
     files: ['**/*.{ts,tsx}'],
@@ -0,0 +1,200 @@
+// Command-line options
+//
+// Parsed before the Tauri builder runs, so `--help`, `--version` and
+// `--list-monitors` answer without opening a window and a bad flag fails
+// before anything is shown.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+use crate::config::profile_file;
+use crate::monitors;
+
+pub const USAGE: &str = "\
+Usage: magic-cursor [OPTIONS] [FILE.magiccursor...]
+
+Options:
+      --profile <NAME>    Switch to the profile NAME
+      --no-click-through  Start with click-through disabled
+      --toggle-click-through
+                          Flip the click-through state
+      --start-hidden      Start with the overlay hidden; show it from the tray
+      --monitor <ID>      Only cover the monitor ID (see --list-monitors)
+      --config <PATH>     Read and write settings in PATH instead of the default file
+      --list-monitors     Print the connected monitors and exit
+  -h, --help              Print this help and exit
+  -V, --version           Print the version and exit
+
+Profile files given as arguments are imported and switched to.
+
+While the app runs, launching it again passes the options to the running
+instance instead of starting a second one; without options it shows the
+overlay.";
+
+/// Startup settings taken from the command line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Options {
+    pub profile: Option<String>,
+    pub no_click_through: bool,
+    pub toggle_click_through: bool,
+    pub start_hidden: bool,
+    /// Output name (e.g. `DP-1`) of the only monitor to cover
+    pub monitor: Option<String>,
+    pub config: Option<PathBuf>,
+    /// Profile files to import, e.g. opened through the file association
+    pub import: Vec<PathBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(Options),
+    ListMonitors,
+    Help,
+    Version,
+}
+
+/// Parses the arguments after the program name.
+///
+/// Values can be given as `--flag value` or `--flag=value`.
+pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let Some(text) = arg.to_str() else {
+            options.import.push(profile_path(arg)?);
+            continue;
+        };
+
+        let (flag, inline_value) = match text.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(OsString::from(value))),
+            _ => (text, None),
+        };
+        let mut value = |name: &str| {
+            inline_value.clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match flag {
+            "--profile" => options.profile = Some(utf8(value(flag)?, flag)?),
+            "--monitor" => options.monitor = Some(utf8(value(flag)?, flag)?),
+            "--config" => options.config = Some(PathBuf::from(value(flag)?)),
+            "--no-click-through" => options.no_click_through = true,
+            "--toggle-click-through" => options.toggle_click_through = true,
+            "--start-hidden" => options.start_hidden = true,
+            "--list-monitors" => return Ok(Command::ListMonitors),
+            "-h" | "--help" => return Ok(Command::Help),
+            "-V" | "--version" => return Ok(Command::Version),
+            _ if flag.starts_with('-') => return Err(format!("Unknown option '{}'", flag)),
+            _ => options.import.push(profile_path(arg)?),
+        }
+    }
+
+    Ok(Command::Run(options))
+}
+
+/// Prints one line per monitor for `--list-monitors`.
+pub fn list_monitors() -> Result<(), String> {
+    let layout = monitors::enumerate()
+        .ok_or_else(|| "Listing monitors needs XRandR, which is not available here".to_string())??;
+
+    for monitor in layout {
+        println!(
+            "{}\t{}x{}+{}+{}{}",
+            monitor.id,
+            monitor.width,
+            monitor.height,
+            monitor.x,
+            monitor.y,
+            if monitor.primary { "\tprimary" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+fn utf8(value: OsString, flag: &str) -> Result<String, String> {
+    value.into_string()
+        .map_err(|value| format!("{} value {:?} is not valid UTF-8", flag, value))
+}
+
+fn profile_path(arg: OsString) -> Result<PathBuf, String> {
+    let path = PathBuf::from(arg);
+    if path.extension().is_some_and(|ext| ext == profile_file::EXTENSION) {
+        Ok(path)
+    } else {
+        Err(format!("Unexpected argument '{}'", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_args(args: &[&str]) -> Result<Command, String> {
+        parse(args.iter().map(OsString::from))
+    }
+
+    fn run(args: &[&str]) -> Options {
+        match parse_args(args) {
+            Ok(Command::Run(options)) => options,
+            other => panic!("{:?} parsed as {:?}", args, other),
+        }
+    }
+
+    #[test]
+    fn no_arguments_run_with_defaults() {
+        assert_eq!(run(&[]), Options::default());
+    }
+
+    #[test]
+    fn values_follow_the_flag_or_an_equals_sign() {
+        let options = run(&["--profile", "Work", "--monitor=DP-1", "--config", "/tmp/a=b.json"]);
+        assert_eq!(options.profile.as_deref(), Some("Work"));
+        assert_eq!(options.monitor.as_deref(), Some("DP-1"));
+        assert_eq!(options.config, Some(PathBuf::from("/tmp/a=b.json")));
+
+        // Only the first '=' separates the value
+        assert_eq!(run(&["--profile=a=b"]).profile.as_deref(), Some("a=b"));
+    }
+
+    #[test]
+    fn switches_and_profile_files() {
+        let options = run(&["--no-click-through", "--start-hidden", "neon.magiccursor", "--toggle-click-through"]);
+        assert!(options.no_click_through);
+        assert!(options.start_hidden);
+        assert!(options.toggle_click_through);
+        assert_eq!(options.import, vec![PathBuf::from("neon.magiccursor")]);
+    }
+
+    #[test]
+    fn commands_stop_parsing() {
+        assert_eq!(parse_args(&["--list-monitors", "--bogus"]), Ok(Command::ListMonitors));
+        assert_eq!(parse_args(&["-h"]), Ok(Command::Help));
+        assert_eq!(parse_args(&["--version"]), Ok(Command::Version));
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        assert_eq!(parse_args(&["--profile"]), Err("--profile needs a value".to_string()));
+        assert_eq!(parse_args(&["--bogus"]), Err("Unknown option '--bogus'".to_string()));
+        assert_eq!(parse_args(&["-x"]), Err("Unknown option '-x'".to_string()));
+        assert_eq!(parse_args(&["notes.txt"]), Err("Unexpected argument 'notes.txt'".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_arguments() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let file = OsString::from_vec(b"caf\xe9.magiccursor".to_vec());
+        let options = match parse([file.clone()]) {
+            Ok(Command::Run(options)) => options,
+            other => panic!("parsed as {:?}", other),
+        };
+        assert_eq!(options.import, vec![PathBuf::from(file)]);
+
+        let name = OsString::from_vec(b"caf\xe9".to_vec());
+        assert!(parse([OsString::from("--profile"), name]).is_err());
+    }
+}
@@ -0,0 +1,325 @@
+// Global keyboard shortcuts
+//
+// Stored as accelerator strings such as `Ctrl+Alt+M`, which stay readable
+// in the config file. An empty string turns a shortcut off.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use ts_rs::TS;
+
+use crate::cursor::Modifiers;
+
+/// Shortcut for each action that can be run from the keyboard.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export)]
+pub struct Hotkeys {
+    pub toggle_click_through: String,
+    /// Pause or resume following the cursor
+    pub toggle_tracking: String,
+    /// Switch to the next profile, wrapping around
+    pub next_profile: String,
+    /// Show or hide every overlay
+    pub toggle_visible: String,
+}
+
+impl Default for Hotkeys {
+    fn default() -> Self {
+        Self {
+            toggle_click_through: "Ctrl+Alt+Shift+M".to_string(),
+            toggle_tracking: "Ctrl+Alt+Shift+P".to_string(),
+            next_profile: "Ctrl+Alt+Shift+N".to_string(),
+            toggle_visible: "Ctrl+Alt+Shift+H".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HotkeyAction {
+    ToggleClickThrough,
+    ToggleTracking,
+    NextProfile,
+    ToggleVisible,
+}
+
+impl HotkeyAction {
+    /// Name of the setting, as in the config file
+    pub fn field(self) -> &'static str {
+        match self {
+            Self::ToggleClickThrough => "toggleClickThrough",
+            Self::ToggleTracking => "toggleTracking",
+            Self::NextProfile => "nextProfile",
+            Self::ToggleVisible => "toggleVisible",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::ToggleClickThrough => "toggle click-through",
+            Self::ToggleTracking => "pause or resume tracking",
+            Self::NextProfile => "switch to the next profile",
+            Self::ToggleVisible => "show or hide the overlay",
+        }
+    }
+}
+
+/// A key together with the modifiers that must be held, e.g. `Ctrl+Alt+M`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hotkey {
+    pub modifiers: Modifiers,
+    /// X11 keysym of the key, lowercase for letters
+    pub keysym: u32,
+}
+
+/// Keys besides letters, digits and F1 to F24, with their keysyms.
+const NAMED_KEYS: &[(&str, u32)] = &[
+    ("Space", 0x0020),
+    ("Tab", 0xff09),
+    ("Return", 0xff0d),
+    ("Pause", 0xff13),
+    ("ScrollLock", 0xff14),
+    ("Escape", 0xff1b),
+    ("Home", 0xff50),
+    ("Left", 0xff51),
+    ("Up", 0xff52),
+    ("Right", 0xff53),
+    ("Down", 0xff54),
+    ("PageUp", 0xff55),
+    ("PageDown", 0xff56),
+    ("End", 0xff57),
+    ("Print", 0xff61),
+    ("Insert", 0xff63),
+    ("Backspace", 0xff08),
+    ("Delete", 0xffff),
+];
+
+const F1: u32 = 0xffbe;
+const MAX_FUNCTION_KEY: u32 = 24;
+
+impl Hotkey {
+    /// Parses an accelerator like `Ctrl+Shift+F5`.
+    ///
+    /// Modifiers are `Ctrl`, `Alt`, `Shift` and `Super` (also `Control`,
+    /// `Meta` and `Win`), in any order and case. The key comes last.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty())
+            .ok_or_else(|| format!("'{}' has no key", text))?;
+
+        let mut modifiers = Modifiers::default();
+        for part in parts {
+            let flag = match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => &mut modifiers.control,
+                "alt" => &mut modifiers.alt,
+                "shift" => &mut modifiers.shift,
+                "super" | "meta" | "win" => &mut modifiers.meta,
+                _ => return Err(format!("'{}' is not a modifier in '{}'", part, text)),
+            };
+            if *flag {
+                return Err(format!("'{}' is repeated in '{}'", part, text));
+            }
+            *flag = true;
+        }
+
+        let keysym = keysym(key).ok_or_else(|| format!("'{}' is not a supported key in '{}'", key, text))?;
+        Ok(Self { modifiers, keysym })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    /// The canonical spelling, e.g. `Ctrl+Alt+M`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (held, name) in [
+            (self.modifiers.control, "Ctrl+"),
+            (self.modifiers.alt, "Alt+"),
+            (self.modifiers.shift, "Shift+"),
+            (self.modifiers.meta, "Super+"),
+        ] {
+            if held {
+                f.write_str(name)?;
+            }
+        }
+        f.write_str(&key_name(self.keysym))
+    }
+}
+
+fn keysym(key: &str) -> Option<u32> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return c.is_ascii_alphanumeric().then(|| c.to_ascii_lowercase() as u32);
+    }
+
+    if let Some(number) = key.strip_prefix(['F', 'f']).and_then(|number| number.parse::<u32>().ok()) {
+        return (1..=MAX_FUNCTION_KEY).contains(&number).then(|| F1 + number - 1);
+    }
+
+    NAMED_KEYS.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, keysym)| *keysym)
+}
+
+fn key_name(keysym: u32) -> String {
+    if let Some(c) = char::from_u32(keysym).filter(char::is_ascii_alphanumeric) {
+        return c.to_ascii_uppercase().to_string();
+    }
+    if (F1..F1 + MAX_FUNCTION_KEY).contains(&keysym) {
+        return format!("F{}", keysym - F1 + 1);
+    }
+    NAMED_KEYS.iter()
+        .find(|(_, other)| *other == keysym)
+        .map_or_else(|| format!("{:#x}", keysym), |(name, _)| name.to_string())
+}
+
+impl Hotkeys {
+    fn entries(&self) -> [(HotkeyAction, &String); 4] {
+        [
+            (HotkeyAction::ToggleClickThrough, &self.toggle_click_through),
+            (HotkeyAction::ToggleTracking, &self.toggle_tracking),
+            (HotkeyAction::NextProfile, &self.next_profile),
+            (HotkeyAction::ToggleVisible, &self.toggle_visible),
+        ]
+    }
+
+    /// The shortcuts that are turned on, and a message for each one that
+    /// can't be parsed or repeats an earlier one.
+    pub fn bindings(&self) -> (Vec<(HotkeyAction, Hotkey)>, Vec<String>) {
+        let mut bindings: Vec<(HotkeyAction, Hotkey)> = Vec::new();
+        let mut errors = Vec::new();
+
+        for (action, text) in self.entries() {
+            if text.trim().is_empty() {
+                continue;
+            }
+            match Hotkey::parse(text) {
+                Ok(hotkey) => match bindings.iter().find(|(_, other)| *other == hotkey) {
+                    Some((other, _)) => errors.push(format!(
+                        "hotkeys.{}: {} is already used to {}",
+                        action.field(), hotkey, other.description()
+                    )),
+                    None => bindings.push((action, hotkey)),
+                },
+                Err(e) => errors.push(format!("hotkeys.{}: {}", action.field(), e)),
+            }
+        }
+
+        (bindings, errors)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let (_, errors) = self.bindings();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Turns off the shortcuts `bindings` rejects, returning why.
+    pub fn disable_invalid(&mut self) -> Vec<String> {
+        let (bindings, errors) = self.bindings();
+        for (action, text) in [
+            (HotkeyAction::ToggleClickThrough, &mut self.toggle_click_through),
+            (HotkeyAction::ToggleTracking, &mut self.toggle_tracking),
+            (HotkeyAction::NextProfile, &mut self.next_profile),
+            (HotkeyAction::ToggleVisible, &mut self.toggle_visible),
+        ] {
+            if !bindings.iter().any(|(bound, _)| *bound == action) {
+                text.clear();
+            }
+        }
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn modifiers(control: bool, alt: bool, shift: bool, meta: bool) -> Modifiers {
+        Modifiers { shift, control, alt, meta }
+    }
+
+    #[test]
+    fn parses_modifiers_in_any_order_and_case() {
+        let hotkey = Hotkey::parse("shift + CONTROL+Super+m").unwrap();
+        assert_eq!(hotkey, Hotkey { modifiers: modifiers(true, false, true, true), keysym: 'm' as u32 });
+
+        assert_eq!(Hotkey::parse("Win+Meta+M"), Err("'Meta' is repeated in 'Win+Meta+M'".to_string()));
+        assert_eq!(Hotkey::parse("Hyper+M"), Err("'Hyper' is not a modifier in 'Hyper+M'".to_string()));
+    }
+
+    #[test]
+    fn parses_letters_digits_function_and_named_keys() {
+        assert_eq!(Hotkey::parse("Ctrl+M"), Hotkey::parse("Ctrl+m"));
+        assert_eq!(Hotkey::parse("Alt+7").unwrap().keysym, '7' as u32);
+        assert_eq!(Hotkey::parse("f1").unwrap().keysym, F1);
+        assert_eq!(Hotkey::parse("F24").unwrap().keysym, F1 + 23);
+        assert_eq!(Hotkey::parse("Ctrl+pageup").unwrap().keysym, 0xff55);
+    }
+
+    #[test]
+    fn rejects_unknown_and_missing_keys() {
+        assert_eq!(Hotkey::parse("Ctrl+F25"), Err("'F25' is not a supported key in 'Ctrl+F25'".to_string()));
+        assert_eq!(Hotkey::parse("Ctrl+é"), Err("'é' is not a supported key in 'Ctrl+é'".to_string()));
+        assert_eq!(Hotkey::parse("Ctrl+"), Err("'Ctrl+' has no key".to_string()));
+        assert!(Hotkey::parse("").is_err());
+    }
+
+    #[test]
+    fn display_is_the_canonical_spelling_parse_reads_back() {
+        for text in ["Ctrl+Alt+Shift+Super+M", "Alt+F12", "Ctrl+Space", "Shift+Delete", "0"] {
+            let hotkey = Hotkey::parse(text).unwrap();
+            assert_eq!(hotkey.to_string(), text);
+            assert_eq!(Hotkey::parse(&hotkey.to_string()), Ok(hotkey));
+        }
+        assert_eq!(Hotkey::parse("super+shift+alt+control+m").unwrap().to_string(), "Ctrl+Alt+Shift+Super+M");
+    }
+
+    #[test]
+    fn empty_shortcuts_are_off() {
+        let hotkeys = Hotkeys {
+            toggle_tracking: String::new(),
+            next_profile: "  ".to_string(),
+            ..Hotkeys::default()
+        };
+        let (bindings, errors) = hotkeys.bindings();
+
+        let actions: Vec<HotkeyAction> = bindings.iter().map(|(action, _)| *action).collect();
+        assert_eq!(actions, vec![HotkeyAction::ToggleClickThrough, HotkeyAction::ToggleVisible]);
+        assert!(errors.is_empty());
+        assert_eq!(hotkeys.validate(), Ok(()));
+    }
+
+    #[test]
+    fn detects_shortcuts_used_twice() {
+        let hotkeys = Hotkeys {
+            toggle_visible: "shift+ctrl+alt+m".to_string(),
+            ..Hotkeys::default()
+        };
+        let (bindings, errors) = hotkeys.bindings();
+
+        assert_eq!(bindings.len(), 3);
+        assert_eq!(errors, vec![
+            "hotkeys.toggleVisible: Ctrl+Alt+Shift+M is already used to toggle click-through".to_string(),
+        ]);
+        assert_eq!(hotkeys.validate(), Err(errors[0].clone()));
+    }
+
+    #[test]
+    fn disable_invalid_turns_off_only_the_rejected_shortcuts() {
+        let mut hotkeys = Hotkeys {
+            toggle_tracking: "Ctrl+Nope".to_string(),
+            toggle_visible: "Ctrl+Alt+Shift+M".to_string(),
+            ..Hotkeys::default()
+        };
+        let errors = hotkeys.disable_invalid();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(hotkeys, Hotkeys {
+            toggle_tracking: String::new(),
+            toggle_visible: String::new(),
+            ..Hotkeys::default()
+        });
+        assert_eq!(hotkeys.disable_invalid(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,65 @@
+use std::ffi::OsString;
+use std::fs;
+use std::mem::MaybeUninit;
+use std::os::fd::OwnedFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use rustix::fs::inotify::{self, CreateFlags, WatchFlags};
+use rustix::io::Errno;
+
+/// inotify watch on the directory holding the config file.
+///
+/// The directory is watched rather than the file itself because editors and
+/// `ConfigStore` replace the file by renaming a new one over it, which would
+/// silently end a watch on the old inode.
+pub struct ConfigWatcher {
+    fd: OwnedFd,
+    file_name: OsString,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: &Path) -> Result<Self, String> {
+        let file_name = path.file_name()
+            .ok_or_else(|| format!("Config path {} has no file name", path.display()))?
+            .to_os_string();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        // The directory may not exist before the first save
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory {}: {}", dir.display(), e))?;
+
+        let fd = inotify::init(CreateFlags::CLOEXEC)
+            .map_err(|e| format!("Failed to create inotify instance: {}", e))?;
+
+        // Only completed writes: IN_CREATE and IN_MODIFY fire while the file
+        // is still partially written
+        inotify::add_watch(&fd, dir, WatchFlags::CLOSE_WRITE | WatchFlags::MOVED_TO)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+        Ok(Self { fd, file_name })
+    }
+
+    /// Blocks until the config file has been written or replaced.
+    pub fn wait_for_change(&mut self) -> Result<(), String> {
+        let mut buf = [MaybeUninit::uninit(); 4096];
+        let mut reader = inotify::Reader::new(&self.fd, &mut buf);
+
+        loop {
+            let event = match reader.next() {
+                Ok(event) => event,
+                Err(Errno::INTR) => continue,
+                Err(e) => return Err(format!("Failed to read inotify events: {}", e)),
+            };
+
+            let is_config = event.file_name()
+                .is_some_and(|name| name.to_bytes() == self.file_name.as_bytes());
+            if is_config {
+                return Ok(());
+            }
+        }
+    }
+}
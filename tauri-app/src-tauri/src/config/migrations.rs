@@ -0,0 +1,200 @@
+// Upgrades of config files written by older versions
+//
+// Each migration rewrites the raw JSON object of one schema version into the
+// next, before it is deserialized, so old field names never reach `Config`.
+
+use serde_json::{Map, Value};
+
+use super::DEFAULT_PROFILE;
+
+/// Upgrades a config object from one schema version to the next.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// Migration `i` turns a version `i + 1` file into a version `i + 2` one.
+/// Append only: never reorder or remove entries.
+const MIGRATIONS: &[Migration] = &[group_palette, move_into_profile];
+
+/// Schema version written to new files.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32 + 1;
+
+/// Upgrades `config` from schema `version` to `SCHEMA_VERSION`.
+pub fn migrate(config: &mut Map<String, Value>, version: u32) -> Result<(), String> {
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migrate(config).map_err(|e| format!("migration from schema {} failed: {}", from + 1, e))?;
+    }
+    Ok(())
+}
+
+/// Schema 2: the color parameters moved into `palette`.
+fn group_palette(config: &mut Map<String, Value>) -> Result<(), String> {
+    let mut palette = Map::new();
+    for (old, new) in [
+        ("colorHueRange", "hueRange"),
+        ("colorSaturation", "saturation"),
+        ("colorBrightness", "brightness"),
+    ] {
+        if let Some(value) = config.remove(old) {
+            palette.insert(new.to_string(), value);
+        }
+    }
+
+    config.insert("palette".to_string(), Value::Object(palette));
+    Ok(())
+}
+
+/// Schema 3: the settings moved into a list of named profiles.
+fn move_into_profile(config: &mut Map<String, Value>) -> Result<(), String> {
+    let mut profile = Map::new();
+    profile.insert("name".to_string(), Value::from(DEFAULT_PROFILE));
+    for key in ["fluid", "palette"] {
+        if let Some(value) = config.remove(key) {
+            profile.insert(key.to_string(), value);
+        }
+    }
+
+    config.insert("profiles".to_string(), Value::Array(vec![Value::Object(profile)]));
+    config.insert("activeProfile".to_string(), Value::from(DEFAULT_PROFILE));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{read_config, Config, FluidConfig, Palette, Profile};
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// The same settings as written by each schema version.
+    const FIXTURES: &[(&str, &str)] = &[
+        (
+            // Before versioning, with no schemaVersion at all
+            "v0",
+            r#"{
+                "fluid": { "CURL": 12.0, "SPLAT_RADIUS": 0.3 },
+                "colorHueRange": [0.2, 0.6],
+                "colorSaturation": 0.5,
+                "colorBrightness": 0.8,
+                "hasSeenWelcome": true
+            }"#,
+        ),
+        (
+            "v1",
+            r#"{
+                "schemaVersion": 1,
+                "fluid": { "CURL": 12.0, "SPLAT_RADIUS": 0.3 },
+                "colorHueRange": [0.2, 0.6],
+                "colorSaturation": 0.5,
+                "colorBrightness": 0.8,
+                "hasSeenWelcome": true
+            }"#,
+        ),
+        (
+            "v2",
+            r#"{
+                "schemaVersion": 2,
+                "fluid": { "CURL": 12.0, "SPLAT_RADIUS": 0.3 },
+                "palette": { "hueRange": [0.2, 0.6], "saturation": 0.5, "brightness": 0.8 },
+                "hasSeenWelcome": true
+            }"#,
+        ),
+        (
+            "v3",
+            r#"{
+                "schemaVersion": 3,
+                "profiles": [{
+                    "name": "Default",
+                    "fluid": { "CURL": 12.0, "SPLAT_RADIUS": 0.3 },
+                    "palette": { "hueRange": [0.2, 0.6], "saturation": 0.5, "brightness": 0.8 }
+                }],
+                "activeProfile": "Default",
+                "hasSeenWelcome": true
+            }"#,
+        ),
+    ];
+
+    fn expected() -> Config {
+        Config {
+            profiles: vec![Profile {
+                fluid: FluidConfig {
+                    curl: 12.0,
+                    splat_radius: 0.3,
+                    ..FluidConfig::default()
+                },
+                palette: Palette {
+                    hue_range: (0.2, 0.6),
+                    saturation: 0.5,
+                    brightness: 0.8,
+                },
+                ..Profile::default()
+            }],
+            has_seen_welcome: true,
+            ..Config::default()
+        }
+    }
+
+    /// A fresh directory for one test's files.
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("magic-cursor-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrates_every_schema_to_the_current_one() {
+        assert_eq!(SCHEMA_VERSION, 3, "add a fixture for the new schema");
+
+        for (name, contents) in FIXTURES {
+            let dir = temp_dir(&format!("migrate-{}", name));
+            let path = dir.join("config.json");
+            fs::write(&path, contents).unwrap();
+
+            let config = read_config(&path).unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(config, expected(), "{}", name);
+
+            // Migrated files are rewritten, keeping the original aside
+            let written: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(written["schemaVersion"], SCHEMA_VERSION, "{}", name);
+            let backups: Vec<_> = fs::read_dir(&dir).unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .filter(|file| file.ends_with(".bak"))
+                .collect();
+            let expected_backups = match *name {
+                "v0" => vec!["config.json.v1.bak".to_string()],
+                "v3" => Vec::new(),
+                _ => vec![format!("config.json.{}.bak", name)],
+            };
+            assert_eq!(backups, expected_backups, "{}", name);
+            assert_eq!(read_config(&path).unwrap(), expected(), "{} after rewrite", name);
+
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn refuses_newer_and_invalid_versions() {
+        for (name, contents) in [
+            ("newer", r#"{ "schemaVersion": 99 }"#),
+            ("zero", r#"{ "schemaVersion": 0 }"#),
+            ("text", r#"{ "schemaVersion": "3" }"#),
+        ] {
+            let dir = temp_dir(&format!("refuse-{}", name));
+            let path = dir.join("config.json");
+            fs::write(&path, contents).unwrap();
+
+            assert!(read_config(&path).is_err(), "{}", name);
+            assert_eq!(fs::read_to_string(&path).unwrap(), contents, "{} was rewritten", name);
+
+            fs::remove_dir_all(&dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn group_palette_keeps_missing_colors_missing() {
+        let mut config = Map::new();
+        config.insert("colorSaturation".to_string(), Value::from(0.5));
+        group_palette(&mut config).unwrap();
+
+        assert_eq!(Value::Object(config), serde_json::json!({ "palette": { "saturation": 0.5 } }));
+    }
+}
@@ -0,0 +1,611 @@
+// Persistent user settings shared by every window
+//
+// Stored as JSON in the app config directory
+// (`$XDG_CONFIG_HOME/com.magiccursor.desktop/config.json` on Linux), so the
+// settings survive a wiped webview data dir and can be provisioned by
+// dropping a file in place.
+//
+// The file is watched while the app runs, so edits made with a text editor
+// apply live.
+//
+// Files carry a `schemaVersion`. Older files are upgraded by running the
+// migrations in `migrations.rs` in order, after copying the original next to it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use ts_rs::TS;
+
+mod hotkeys;
+#[cfg(target_os = "linux")]
+mod inotify;
+mod migrations;
+pub mod profile_file;
+mod profiles;
+pub mod rules;
+pub mod validation;
+
+pub use hotkeys::{Hotkey, HotkeyAction, Hotkeys};
+pub use profiles::{Behavior, Profile, DEFAULT_PROFILE};
+pub use rules::{AppRule, RuleAction};
+use migrations::SCHEMA_VERSION;
+use validation::describe;
+
+pub const FILE_NAME: &str = "config.json";
+
+/// Fluid simulation parameters, named like the `MagicMouse` props.
+///
+/// `validate` and `clamp` enforce the ranges below.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+#[ts(export)]
+pub struct FluidConfig {
+    /// Pressure solver strength, 0 to 1
+    pub pressure: f64,
+    /// Vorticity (swirl) strength, 0 to 30
+    pub curl: f64,
+    /// Size of the cursor splat, 0.05 to 0.5
+    pub splat_radius: f64,
+    /// Strength of the cursor splat, 1000 to 20000
+    pub splat_force: f64,
+    /// How quickly color fades, 0.1 to 10
+    pub density_dissipation: f64,
+    /// How quickly motion slows, 0.1 to 10
+    pub velocity_dissipation: f64,
+    /// How fast the colors cycle, 1 to 50
+    pub color_update_speed: f64,
+    pub shading: bool,
+}
+
+impl Default for FluidConfig {
+    fn default() -> Self {
+        Self {
+            pressure: 1.0,
+            curl: 4.0,
+            splat_radius: 0.2,
+            splat_force: 6000.0,
+            density_dissipation: 3.5,
+            velocity_dissipation: 2.0,
+            color_update_speed: 10.0,
+            shading: false,
+        }
+    }
+}
+
+/// Colors of the splats.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export)]
+pub struct Palette {
+    /// Hue range of generated splat colors, each end 0 to 1
+    pub hue_range: (f64, f64),
+    /// 0 to 1
+    pub saturation: f64,
+    /// 0 to 1
+    pub brightness: f64,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            hue_range: (0.0, 1.0),
+            saturation: 1.0,
+            brightness: 1.0,
+        }
+    }
+}
+
+/// Everything the frontend used to keep in localStorage.
+///
+/// Missing fields fall back to their defaults, so partial files still load.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export)]
+pub struct Config {
+    pub profiles: Vec<Profile>,
+    /// Name of the profile in use
+    pub active_profile: String,
+    pub has_seen_welcome: bool,
+    pub hotkeys: Hotkeys,
+    /// What to do while a full-screen application has focus
+    pub fullscreen_app: FullscreenAction,
+    /// Applied while the application they name has focus
+    pub rules: Vec<AppRule>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum FullscreenAction {
+    /// Keep the overlay as it is
+    Ignore,
+    /// Stop following the cursor on that monitor, letting the fluid fade out
+    Pause,
+    /// Hide the overlay on that monitor
+    #[default]
+    Hide,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            profiles: vec![Profile::default()],
+            active_profile: DEFAULT_PROFILE.to_string(),
+            has_seen_welcome: false,
+            hotkeys: Hotkeys::default(),
+            fullscreen_app: FullscreenAction::default(),
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// On-disk layout: the config plus its schema version.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigFile<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    config: &'a Config,
+}
+
+/// The config file and its current contents.
+pub struct ConfigStore {
+    path: PathBuf,
+    config: Config,
+    warning: Option<String>,
+    /// No file existed when the app started
+    is_new: bool,
+    /// What our last `set` wrote, to tell its change events from edits
+    written: Option<String>,
+    /// The user's own profile while `config` uses one a rule switched to
+    saved_profile: Option<String>,
+}
+
+impl ConfigStore {
+    /// Default location of the config file for this app.
+    pub fn default_path(app_config: &tauri::Config) -> Option<PathBuf> {
+        tauri::api::path::app_config_dir(app_config).map(|dir| dir.join(FILE_NAME))
+    }
+
+    /// Reads the config at `path`, migrating it to the current schema.
+    ///
+    /// A missing file yields the defaults. So does one that can't be read,
+    /// after it has been copied aside; the reason is kept in `warning`.
+    /// Out-of-range values are clamped, invalid shortcuts turned off and
+    /// invalid rules dropped, also with a warning.
+    pub fn load(path: PathBuf) -> Self {
+        let is_new = !path.exists();
+        let (config, warning) = match read_config(&path) {
+            Ok(mut config) => {
+                let mut problems = Vec::new();
+                let clamped = config.clamp();
+                if !clamped.is_empty() {
+                    problems.push(format!(
+                        "Clamped out-of-range settings in {}: {}.",
+                        path.display(), describe(&clamped)
+                    ));
+                }
+                let disabled = config.hotkeys.disable_invalid();
+                if !disabled.is_empty() {
+                    problems.push(format!(
+                        "Turned off invalid shortcuts in {}: {}.",
+                        path.display(), disabled.join("; ")
+                    ));
+                }
+                let removed = rules::remove_invalid(&mut config.rules);
+                if !removed.is_empty() {
+                    problems.push(format!(
+                        "Dropped invalid rules in {}: {}.",
+                        path.display(), removed.join("; ")
+                    ));
+                }
+                let warning = (!problems.is_empty()).then(|| problems.join(" "));
+                if let Some(warning) = &warning {
+                    eprintln!("Warning: {}", warning);
+                }
+                (config, warning)
+            }
+            Err(e) => {
+                let fallback = match backup(&path, "corrupt") {
+                    Ok(backup_path) => format!("a copy was saved to {}", backup_path.display()),
+                    Err(e) => format!("no copy could be saved ({})", e),
+                };
+                let warning = format!(
+                    "Could not load {}: {}. Using default settings; {}.",
+                    path.display(), e, fallback
+                );
+                eprintln!("Warning: {}", warning);
+                (Config::default(), Some(warning))
+            }
+        };
+
+        Self {
+            path,
+            config,
+            warning,
+            is_new,
+            written: None,
+            saved_profile: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// True when the app started without a config file, e.g. on the first
+    /// run after upgrading from a version that used localStorage.
+    pub fn is_new(&self) -> bool {
+        self.is_new
+    }
+
+    /// The config in use, with the profile of a rule active while there is one.
+    pub fn get(&self) -> &Config {
+        &self.config
+    }
+
+    /// The config as the user chose it, with their own profile active.
+    fn saved(&self) -> Config {
+        let mut config = self.config.clone();
+        if let Some(profile) = &self.saved_profile {
+            config.active_profile = profile.clone();
+        }
+        config
+    }
+
+    /// Maps a config based on `get` back to the user's choices: the profile
+    /// a rule switched to stands for their own.
+    pub fn as_saved(&self, mut config: Config) -> Config {
+        if let Some(profile) = &self.saved_profile {
+            if config.active_profile == self.config.active_profile {
+                config.active_profile = profile.clone();
+            }
+        }
+        config
+    }
+
+    /// Uses the profile `name` without saving it, or the user's own again
+    /// with `None`. Returns true when the profile in use changed.
+    pub fn use_profile(&mut self, name: Option<&str>) -> Result<bool, String> {
+        if let Some(name) = name {
+            self.config.profile(name)?;
+        }
+        let saved = self.saved_profile.take().unwrap_or_else(|| self.config.active_profile.clone());
+        let active = name.map_or_else(|| saved.clone(), str::to_string);
+        let changed = active != self.config.active_profile;
+
+        if active != saved {
+            self.saved_profile = Some(saved);
+        }
+        self.config.active_profile = active;
+        Ok(changed)
+    }
+
+    /// Applies `change` to a copy of the saved config and saves the result.
+    pub fn update<F>(&mut self, change: F) -> Result<bool, String>
+    where
+        F: FnOnce(&mut Config) -> Result<(), String>,
+    {
+        let mut config = self.saved();
+        change(&mut config)?;
+        self.set(config)
+    }
+
+    /// Re-reads the file after it changed on disk.
+    ///
+    /// An invalid file is rejected and the current config kept, so a typo
+    /// in an editor never resets the settings. Returns true when the config
+    /// changed. The change events of our own `set` are skipped, keeping any
+    /// warning; otherwise it is cleared once the file loads cleanly.
+    pub fn reload(&mut self) -> Result<bool, String> {
+        // Deleting the file keeps the settings; the next `set` recreates it
+        if !self.path.exists() {
+            return Ok(false);
+        }
+        if self.written.is_some() && fs::read_to_string(&self.path).ok() == self.written {
+            return Ok(false);
+        }
+
+        let config = read_config(&self.path)
+            .and_then(|config| config.validate().map(|()| config).map_err(|errors| describe(&errors)))
+            .and_then(|config| config.hotkeys.validate().map(|()| config))
+            .and_then(|config| rules::validate(&config.rules).map(|()| config))
+            .map_err(|e| {
+                let warning = format!(
+                    "Ignored changes to {}: {}. Keeping the current settings.",
+                    self.path.display(), e
+                );
+                self.warning = Some(warning.clone());
+                warning
+            })?;
+
+        self.warning = None;
+        if config == self.saved() {
+            return Ok(false);
+        }
+
+        self.make_current(config);
+        Ok(true)
+    }
+
+    /// Records a problem to show in the UI until the next successful save.
+    pub fn warn(&mut self, warning: String) {
+        self.warning = Some(warning);
+    }
+
+    /// Why the file on disk was not used or a profile import failed, if so.
+    pub fn warning(&self) -> Option<&str> {
+        self.warning.as_deref()
+    }
+
+    /// Writes `config` to disk and makes it current.
+    ///
+    /// Returns false when `config` equals the saved one, in which case
+    /// nothing is written.
+    pub fn set(&mut self, mut config: Config) -> Result<bool, String> {
+        config.check_profiles().map_err(|e| format!("Invalid config: {}", e))?;
+        config.validate().map_err(|errors| format!("Invalid config: {}", describe(&errors)))?;
+        config.hotkeys.validate().map_err(|e| format!("Invalid config: {}", e))?;
+        rules::validate(&config.rules).map_err(|e| format!("Invalid config: {}", e))?;
+        if config == self.saved() {
+            return Ok(false);
+        }
+
+        self.written = Some(write_atomically(&self.path, &config)?);
+        self.make_current(config);
+        self.warning = None;
+        Ok(true)
+    }
+
+    /// Makes the saved `config` current. A rule's profile stays in use
+    /// unless the user switched profiles or it no longer exists.
+    fn make_current(&mut self, mut config: Config) {
+        if let Some(saved) = self.saved_profile.take() {
+            let temporary = &self.config.active_profile;
+            if config.active_profile == saved && config.profile(temporary).is_ok() {
+                config.active_profile = temporary.clone();
+                self.saved_profile = Some(saved);
+            }
+        }
+        self.config = config;
+    }
+}
+
+/// Calls `on_change` whenever the file at `path` is written or replaced.
+///
+/// Never returns unless watching fails. Uses inotify on Linux and polls the
+/// modification time elsewhere.
+pub fn watch<F: FnMut()>(path: &Path, mut on_change: F) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut watcher = inotify::ConfigWatcher::new(path)?;
+        loop {
+            watcher.wait_for_change()?;
+            on_change();
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let modified = || fs::metadata(path).and_then(|metadata| metadata.modified()).ok();
+
+        let mut last = modified();
+        loop {
+            std::thread::sleep(std::time::Duration::from_secs(1));
+
+            let current = modified();
+            if current != last {
+                last = current;
+                if current.is_some() {
+                    on_change();
+                }
+            }
+        }
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config, String> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        Err(e) => return Err(format!("failed to read file: {}", e)),
+    };
+
+    let mut object = match serde_json::from_str(&contents) {
+        Ok(Value::Object(object)) => object,
+        Ok(_) => return Err("expected a JSON object".to_string()),
+        Err(e) => return Err(format!("invalid JSON: {}", e)),
+    };
+
+    // Files from before versioning have no schemaVersion
+    let version = match object.remove("schemaVersion") {
+        None => 1,
+        Some(value) => value.as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .filter(|version| *version >= 1)
+            .ok_or_else(|| format!("invalid schemaVersion {}", value))?,
+    };
+
+    if version > SCHEMA_VERSION {
+        return Err(format!(
+            "schema version {} is newer than this app supports ({})",
+            version, SCHEMA_VERSION
+        ));
+    }
+
+    if version < SCHEMA_VERSION {
+        let backup_path = backup(path, &format!("v{}", version))
+            .map_err(|e| format!("refusing to migrate without a backup: {}", e))?;
+
+        migrations::migrate(&mut object, version)?;
+        println!(
+            "Migrated config from schema {} to {} (original saved to {})",
+            version, SCHEMA_VERSION, backup_path.display()
+        );
+    }
+
+    let mut config: Config = serde_json::from_value(Value::Object(object))
+        .map_err(|e| format!("invalid settings: {}", e))?;
+    config.check_profiles()?;
+
+    if version < SCHEMA_VERSION {
+        if let Err(e) = write_atomically(path, &config) {
+            eprintln!("Warning: failed to save migrated config: {}", e);
+        }
+    }
+    Ok(config)
+}
+
+/// Copies `path` to `<path>.<tag>.bak`, replacing an older copy.
+fn backup(path: &Path, tag: &str) -> Result<PathBuf, String> {
+    let backup_path = sibling(path, &format!("{}.bak", tag));
+    fs::copy(path, &backup_path)
+        .map_err(|e| format!("failed to copy {} to {}: {}", path.display(), backup_path.display(), e))?;
+    Ok(backup_path)
+}
+
+/// Writes through a temporary file so a crash never leaves half a config.
+/// Returns what was written.
+fn write_atomically(path: &Path, config: &Config) -> Result<String, String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create config directory {}: {}", dir.display(), e))?;
+    }
+
+    let file = ConfigFile {
+        schema_version: SCHEMA_VERSION,
+        config,
+    };
+    let contents = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    let temp_path = sibling(path, "tmp");
+    fs::write(&temp_path, &contents)
+        .map_err(|e| format!("Failed to write {}: {}", temp_path.display(), e))?;
+    fs::rename(&temp_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    Ok(contents)
+}
+
+/// `<path>.<suffix>`, keeping the original extension.
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A config file in a fresh directory of its own.
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("magic-cursor-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join(FILE_NAME)
+    }
+
+    #[test]
+    fn reload_skips_our_own_writes() {
+        let path = temp_file("own-writes");
+        let mut store = ConfigStore::load(path.clone());
+        let mut config = store.get().clone();
+        config.has_seen_welcome = true;
+        store.set(config).unwrap();
+        store.warn("Imported profile was invalid".to_string());
+
+        assert_eq!(store.reload(), Ok(false));
+        assert_eq!(store.warning(), Some("Imported profile was invalid"));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn reload_keeps_the_warning_until_the_file_loads() {
+        let path = temp_file("edits");
+        let mut store = ConfigStore::load(path.clone());
+        fs::write(&path, "{ not json").unwrap();
+        assert!(store.reload().is_err());
+        assert!(store.warning().is_some());
+
+        fs::write(&path, r#"{ "schemaVersion": 3, "hasSeenWelcome": true }"#).unwrap();
+        assert_eq!(store.reload(), Ok(true));
+        assert!(store.get().has_seen_welcome);
+        assert_eq!(store.warning(), None);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    /// A store holding the profiles `Default`, `Calm` and `Vivid`.
+    fn with_profiles(name: &str) -> (PathBuf, ConfigStore) {
+        let path = temp_file(name);
+        let mut store = ConfigStore::load(path.clone());
+        store.update(|config| {
+            config.create_profile("Calm")?;
+            config.create_profile("Vivid")
+        }).unwrap();
+        (path, store)
+    }
+
+    #[test]
+    fn profiles_used_for_rules_are_not_saved() {
+        let (path, mut store) = with_profiles("rule-profile");
+        assert_eq!(store.use_profile(Some("Calm")), Ok(true));
+        assert_eq!(store.get().active_profile, "Calm");
+
+        store.update(|config| {
+            config.has_seen_welcome = true;
+            Ok(())
+        }).unwrap();
+        assert_eq!(store.get().active_profile, "Calm");
+        assert_eq!(read_config(&path).unwrap().active_profile, DEFAULT_PROFILE);
+
+        assert_eq!(store.use_profile(None), Ok(true));
+        assert_eq!(store.get().active_profile, DEFAULT_PROFILE);
+        assert_eq!(store.use_profile(None), Ok(false));
+        assert!(store.use_profile(Some("Missing")).is_err());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn switching_by_hand_ends_the_rule_profile() {
+        let (path, mut store) = with_profiles("hand-switch");
+        store.use_profile(Some("Calm")).unwrap();
+        store.update(|config| config.switch_profile("Vivid")).unwrap();
+        assert_eq!(store.get().active_profile, "Vivid");
+
+        // Nothing is left to restore
+        assert_eq!(store.use_profile(None), Ok(false));
+        assert_eq!(store.get().active_profile, "Vivid");
+        assert_eq!(read_config(&path).unwrap().active_profile, "Vivid");
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn deleting_the_rule_profile_goes_back_to_the_saved_one() {
+        let (path, mut store) = with_profiles("rule-profile-deleted");
+        store.use_profile(Some("Calm")).unwrap();
+        store.update(|config| config.delete_profile("Calm")).unwrap();
+
+        assert_eq!(store.get().active_profile, DEFAULT_PROFILE);
+        assert_eq!(store.use_profile(None), Ok(false));
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn configs_from_the_panels_keep_the_saved_profile() {
+        let (path, mut store) = with_profiles("panel-config");
+        store.use_profile(Some("Calm")).unwrap();
+        let mut shown = store.get().clone();
+        shown.has_seen_welcome = true;
+        let config = store.as_saved(shown);
+        assert_eq!(config.active_profile, DEFAULT_PROFILE);
+
+        store.set(config).unwrap();
+        assert_eq!(store.get().active_profile, "Calm");
+        assert_eq!(read_config(&path).unwrap().active_profile, DEFAULT_PROFILE);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
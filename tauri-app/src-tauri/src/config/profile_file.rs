@@ -0,0 +1,81 @@
+// Shareable profile files (`.magiccursor`)
+//
+// One profile wrapped in a small header naming the format, so a file can be
+// recognized on its own and rejected cleanly by versions that can't read it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::validation::describe;
+use super::Profile;
+
+pub const EXTENSION: &str = "magiccursor";
+const FORMAT: &str = "magiccursor-profile";
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileFile {
+    format: String,
+    format_version: u32,
+    profile: Profile,
+}
+
+/// Writes `profile` to `path`, adding the extension when it has none.
+pub fn export(profile: &Profile, path: &Path) -> Result<PathBuf, String> {
+    let path = if path.extension().is_none() {
+        path.with_extension(EXTENSION)
+    } else {
+        path.to_path_buf()
+    };
+
+    let file = ProfileFile {
+        format: FORMAT.to_string(),
+        format_version: FORMAT_VERSION,
+        profile: profile.clone(),
+    };
+    let contents = serde_json::to_string_pretty(&file)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    fs::write(&path, contents)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+/// Reads and checks the profile in a file written by `export`.
+///
+/// The name is taken as is; the caller resolves clashes with existing
+/// profiles. A file without a name is named after the file.
+pub fn read(path: &Path) -> Result<Profile, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let file: Value = serde_json::from_str(&contents)
+        .map_err(|e| format!("{} is not valid JSON: {}", path.display(), e))?;
+
+    if file.get("format").and_then(Value::as_str) != Some(FORMAT) {
+        return Err(format!("{} is not a Magic Cursor profile", path.display()));
+    }
+    let version = file.get("formatVersion")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| format!("{} has no valid formatVersion", path.display()))?;
+    if version > u64::from(FORMAT_VERSION) {
+        return Err(format!(
+            "{} uses profile format {}, which is newer than this app supports ({})",
+            path.display(), version, FORMAT_VERSION
+        ));
+    }
+
+    let file: ProfileFile = serde_json::from_value(file)
+        .map_err(|e| format!("{} has invalid settings: {}", path.display(), e))?;
+    let mut profile = file.profile;
+    profile.validate()
+        .map_err(|errors| format!("{} has invalid settings: {}", path.display(), describe(&errors)))?;
+
+    if profile.name.trim().is_empty() {
+        profile.name = path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+    }
+    Ok(profile)
+}
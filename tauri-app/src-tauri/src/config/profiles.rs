@@ -0,0 +1,332 @@
+// Named bundles of fluid settings, colors and behavior flags
+//
+// Exactly one profile is active at a time; the overlays render from it.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use super::{Config, FluidConfig, Palette};
+
+pub const DEFAULT_PROFILE: &str = "Default";
+const MAX_NAME_LENGTH: usize = 64;
+
+/// How the overlay behaves while a profile is active.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export)]
+pub struct Behavior {
+    /// Click-through state applied when the profile is activated
+    pub click_through: bool,
+    /// Splat on global primary clicks
+    pub react_to_clicks: bool,
+    /// Push the fluid on global wheel events
+    pub react_to_scroll: bool,
+}
+
+impl Default for Behavior {
+    fn default() -> Self {
+        Self {
+            click_through: true,
+            react_to_clicks: true,
+            react_to_scroll: true,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export)]
+pub struct Profile {
+    /// Unique, shown in the tray
+    pub name: String,
+    pub fluid: FluidConfig,
+    pub palette: Palette,
+    pub behavior: Behavior,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PROFILE.to_string(),
+            fluid: FluidConfig::default(),
+            palette: Palette::default(),
+            behavior: Behavior::default(),
+        }
+    }
+}
+
+impl Config {
+    /// The profile the overlays render from.
+    pub fn active(&self) -> Option<&Profile> {
+        self.profiles.iter().find(|profile| profile.name == self.active_profile)
+    }
+
+    /// Adds a profile with default settings.
+    pub fn create_profile(&mut self, name: &str) -> Result<(), String> {
+        let name = self.check_new_name(name)?;
+        self.profiles.push(Profile::new(&name));
+        Ok(())
+    }
+
+    /// Adds a copy of `name` called `new_name`.
+    pub fn duplicate_profile(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let index = self.profile_index(name)?;
+        let new_name = self.check_new_name(new_name)?;
+
+        let copy = Profile {
+            name: new_name,
+            ..self.profiles[index].clone()
+        };
+        self.profiles.insert(index + 1, copy);
+        Ok(())
+    }
+
+    pub fn rename_profile(&mut self, name: &str, new_name: &str) -> Result<(), String> {
+        let index = self.profile_index(name)?;
+        if new_name.trim() == name {
+            return Ok(());
+        }
+        let new_name = self.check_new_name(new_name)?;
+
+        if self.active_profile == name {
+            self.active_profile = new_name.clone();
+        }
+        self.profiles[index].name = new_name;
+        Ok(())
+    }
+
+    /// Removes a profile. Deleting the active one activates the first of
+    /// the remaining profiles.
+    pub fn delete_profile(&mut self, name: &str) -> Result<(), String> {
+        let index = self.profile_index(name)?;
+        if self.profiles.len() == 1 {
+            return Err("The last profile cannot be deleted".to_string());
+        }
+
+        self.profiles.remove(index);
+        if self.active_profile == name {
+            self.active_profile = self.profiles[0].name.clone();
+        }
+        Ok(())
+    }
+
+    /// Adds an imported profile, renaming it to "Name (2)", "Name (3)", …
+    /// when the name is taken. Returns the name it was added under.
+    pub fn import_profile(&mut self, mut profile: Profile) -> String {
+        let base: String = profile.name.trim().chars().take(MAX_NAME_LENGTH).collect();
+        let base = if base.is_empty() { DEFAULT_PROFILE.to_string() } else { base };
+
+        let mut name = base.clone();
+        let mut copy = 2;
+        while self.profiles.iter().any(|other| other.name == name) {
+            let suffix = format!(" ({})", copy);
+            let stem: String = base.chars().take(MAX_NAME_LENGTH - suffix.chars().count()).collect();
+            name = format!("{}{}", stem.trim_end(), suffix);
+            copy += 1;
+        }
+
+        profile.name = name.clone();
+        self.profiles.push(profile);
+        name
+    }
+
+    pub fn profile(&self, name: &str) -> Result<&Profile, String> {
+        self.profile_index(name).map(|index| &self.profiles[index])
+    }
+
+    pub fn switch_profile(&mut self, name: &str) -> Result<(), String> {
+        self.profile_index(name)?;
+        self.active_profile = name.to_string();
+        Ok(())
+    }
+
+    /// Switches to the profile after the active one, wrapping around.
+    pub fn switch_to_next_profile(&mut self) {
+        let next = self.profiles.iter()
+            .position(|profile| profile.name == self.active_profile)
+            .map_or(0, |index| (index + 1) % self.profiles.len());
+        if let Some(profile) = self.profiles.get(next) {
+            self.active_profile = profile.name.clone();
+        }
+    }
+
+    /// Checks the profile list and points a dangling `active_profile` at
+    /// the first profile.
+    pub fn check_profiles(&mut self) -> Result<(), String> {
+        if self.profiles.is_empty() {
+            return Err("at least one profile is required".to_string());
+        }
+
+        for (index, profile) in self.profiles.iter().enumerate() {
+            if profile.name.trim().is_empty() {
+                return Err(format!("profile {} has no name", index + 1));
+            }
+            if self.profiles[..index].iter().any(|other| other.name == profile.name) {
+                return Err(format!("profile name '{}' is used twice", profile.name));
+            }
+        }
+
+        if self.active().is_none() {
+            self.active_profile = self.profiles[0].name.clone();
+        }
+        Ok(())
+    }
+
+    fn profile_index(&self, name: &str) -> Result<usize, String> {
+        self.profiles.iter()
+            .position(|profile| profile.name == name)
+            .ok_or_else(|| format!("Profile '{}' does not exist", name))
+    }
+
+    fn check_new_name(&self, name: &str) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+        if name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!("Profile name is longer than {} characters", MAX_NAME_LENGTH));
+        }
+        if self.profiles.iter().any(|profile| profile.name == name) {
+            return Err(format!("Profile '{}' already exists", name));
+        }
+        Ok(name.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(names: &[&str]) -> Config {
+        Config {
+            profiles: names.iter().map(|name| Profile::new(name)).collect(),
+            active_profile: names[0].to_string(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn import_keeps_a_free_name() {
+        let mut config = config_with(&["Default"]);
+        assert_eq!(config.import_profile(Profile::new("Neon")), "Neon");
+        assert_eq!(config.profiles[1].name, "Neon");
+    }
+
+    #[test]
+    fn import_numbers_clashing_names() {
+        let mut config = config_with(&["Default", "Neon", "Neon (2)"]);
+        assert_eq!(config.import_profile(Profile::new("Neon")), "Neon (3)");
+        assert_eq!(config.import_profile(Profile::new(" Neon ")), "Neon (4)");
+        assert_eq!(config.import_profile(Profile::new("Default")), "Default (2)");
+    }
+
+    #[test]
+    fn import_names_blank_profiles_default() {
+        let mut config = config_with(&["Work"]);
+        assert_eq!(config.import_profile(Profile::new("  ")), DEFAULT_PROFILE);
+        assert_eq!(config.import_profile(Profile::new("")), "Default (2)");
+    }
+
+    #[test]
+    fn import_shortens_long_names_to_fit_the_suffix() {
+        let long = "x".repeat(MAX_NAME_LENGTH + 10);
+        let mut config = config_with(&["Default"]);
+
+        let first = config.import_profile(Profile::new(&long));
+        assert_eq!(first, "x".repeat(MAX_NAME_LENGTH));
+        let second = config.import_profile(Profile::new(&long));
+        assert_eq!(second, format!("{} (2)", "x".repeat(MAX_NAME_LENGTH - 4)));
+        assert!(config.check_profiles().is_ok());
+    }
+
+    fn names(config: &Config) -> Vec<&str> {
+        config.profiles.iter().map(|profile| profile.name.as_str()).collect()
+    }
+
+    #[test]
+    fn create_adds_a_default_profile_under_a_free_name() {
+        let mut config = config_with(&["Default"]);
+        config.create_profile("  Calm ").unwrap();
+        assert_eq!(config.profile("Calm"), Ok(&Profile::new("Calm")));
+
+        assert_eq!(config.create_profile("Calm"), Err("Profile 'Calm' already exists".to_string()));
+        assert_eq!(config.create_profile(" "), Err("Profile name cannot be empty".to_string()));
+        assert!(config.create_profile(&"x".repeat(MAX_NAME_LENGTH + 1)).is_err());
+        assert_eq!(names(&config), ["Default", "Calm"]);
+        assert_eq!(config.active_profile, "Default");
+    }
+
+    #[test]
+    fn duplicate_copies_the_settings_next_to_the_original() {
+        let mut config = config_with(&["Default", "Neon"]);
+        config.profiles[0].behavior.click_through = false;
+        config.duplicate_profile("Default", "Default copy").unwrap();
+
+        assert_eq!(names(&config), ["Default", "Default copy", "Neon"]);
+        assert!(!config.profiles[1].behavior.click_through);
+        assert!(config.duplicate_profile("Default", "Neon").is_err());
+        assert!(config.duplicate_profile("Nope", "Other").is_err());
+    }
+
+    #[test]
+    fn rename_follows_the_active_profile() {
+        let mut config = config_with(&["Default", "Neon"]);
+        config.rename_profile("Default", "Everyday").unwrap();
+        assert_eq!(names(&config), ["Everyday", "Neon"]);
+        assert_eq!(config.active_profile, "Everyday");
+
+        config.rename_profile("Neon", "Bright").unwrap();
+        assert_eq!(config.active_profile, "Everyday");
+        config.rename_profile("Bright", " Bright ").unwrap();
+        assert_eq!(names(&config), ["Everyday", "Bright"]);
+
+        assert_eq!(config.rename_profile("Bright", "Everyday"), Err("Profile 'Everyday' already exists".to_string()));
+        assert!(config.rename_profile("Bright", "").is_err());
+        assert!(config.rename_profile("Nope", "Other").is_err());
+    }
+
+    #[test]
+    fn delete_refuses_the_last_profile() {
+        let mut config = config_with(&["Default"]);
+        assert_eq!(config.delete_profile("Default"), Err("The last profile cannot be deleted".to_string()));
+        assert!(config.delete_profile("Nope").is_err());
+        assert_eq!(names(&config), ["Default"]);
+    }
+
+    #[test]
+    fn deleting_the_active_profile_activates_the_first() {
+        let mut config = config_with(&["Default", "Neon", "Calm"]);
+        config.switch_profile("Neon").unwrap();
+        config.delete_profile("Neon").unwrap();
+        assert_eq!(config.active_profile, "Default");
+
+        config.delete_profile("Default").unwrap();
+        assert_eq!(names(&config), ["Calm"]);
+        assert_eq!(config.active_profile, "Calm");
+    }
+
+    #[test]
+    fn switch_to_next_wraps_around() {
+        let mut config = config_with(&["Default", "Neon", "Calm"]);
+        assert!(config.switch_profile("Nope").is_err());
+        config.switch_profile("Neon").unwrap();
+
+        config.switch_to_next_profile();
+        assert_eq!(config.active_profile, "Calm");
+        config.switch_to_next_profile();
+        assert_eq!(config.active_profile, "Default");
+
+        config.active_profile = "Gone".to_string();
+        config.switch_to_next_profile();
+        assert_eq!(config.active_profile, "Default");
+    }
+}
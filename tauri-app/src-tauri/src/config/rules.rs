@@ -0,0 +1,233 @@
+// Per-application rules
+//
+// Each rule names an application by its X11 `WM_CLASS`, process name or a
+// window title pattern, and what to do while it has focus. The first rule
+// that matches the focused window applies.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+use crate::focus::FocusedWindow;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(rename_all = "camelCase", default)]
+#[ts(export)]
+pub struct AppRule {
+    /// Instance or class name from `WM_CLASS`, e.g. `firefox`; any case
+    pub wm_class: Option<String>,
+    /// Name of the owning process, e.g. `code`
+    pub process: Option<String>,
+    /// Regular expression searched for in the window title
+    pub title: Option<String>,
+    pub action: RuleAction,
+}
+
+/// What a rule does while its application has focus; undone afterwards.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(export)]
+pub enum RuleAction {
+    /// Stop following the cursor
+    #[default]
+    Pause,
+    /// Activate another profile
+    #[serde(rename_all = "camelCase")]
+    SwitchProfile { profile: String },
+    /// Scale the splat force and brightness, 0 to 1
+    #[serde(rename_all = "camelCase")]
+    LowerIntensity { intensity: f64 },
+}
+
+impl AppRule {
+    /// Checks that the rule names an application and its values are usable.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.wm_class.is_none() && self.process.is_none() && self.title.is_none() {
+            return Err("needs wmClass, process or title".to_string());
+        }
+        if let Some(title) = &self.title {
+            Regex::new(title).map_err(|e| format!("title is not a valid pattern: {}", e))?;
+        }
+        if let RuleAction::LowerIntensity { intensity } = self.action {
+            if !(0.0..=1.0).contains(&intensity) {
+                return Err(format!("intensity {} is not between 0 and 1", intensity));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks every rule, naming the first invalid one.
+pub fn validate(rules: &[AppRule]) -> Result<(), String> {
+    for (index, rule) in rules.iter().enumerate() {
+        rule.validate().map_err(|e| format!("rules[{}]: {}", index, e))?;
+    }
+    Ok(())
+}
+
+/// Drops the rules `validate` rejects, returning why.
+pub fn remove_invalid(rules: &mut Vec<AppRule>) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut index = 0;
+    rules.retain(|rule| {
+        let result = rule.validate();
+        if let Err(e) = &result {
+            errors.push(format!("rules[{}]: {}", index, e));
+        }
+        index += 1;
+        result.is_ok()
+    });
+    errors
+}
+
+/// Rules ready to match, with their title patterns compiled.
+#[derive(Default)]
+pub struct RuleSet {
+    rules: Vec<AppRule>,
+    titles: Vec<Option<Regex>>,
+}
+
+impl RuleSet {
+    /// Compiles `rules`; invalid title patterns never match.
+    pub fn new(rules: &[AppRule]) -> Self {
+        Self {
+            rules: rules.to_vec(),
+            titles: rules.iter()
+                .map(|rule| rule.title.as_deref().and_then(|title| Regex::new(title).ok()))
+                .collect(),
+        }
+    }
+
+    /// Whether this was compiled from `rules`.
+    pub fn is_for(&self, rules: &[AppRule]) -> bool {
+        self.rules == rules
+    }
+
+    /// The first rule matching `window`.
+    pub fn find(&self, window: &FocusedWindow) -> Option<&AppRule> {
+        self.rules.iter().zip(&self.titles)
+            .find(|(rule, title)| matches(rule, title.as_ref(), window))
+            .map(|(rule, _)| rule)
+    }
+}
+
+/// True when every criterion the rule sets holds for `window`.
+fn matches(rule: &AppRule, title: Option<&Regex>, window: &FocusedWindow) -> bool {
+    if let Some(wm_class) = &rule.wm_class {
+        if !wm_class.eq_ignore_ascii_case(&window.instance) && !wm_class.eq_ignore_ascii_case(&window.class) {
+            return false;
+        }
+    }
+    if let Some(process) = &rule.process {
+        if window.process.as_deref() != Some(process.as_str()) {
+            return false;
+        }
+    }
+    if rule.title.is_some() {
+        // An invalid pattern matches nothing
+        if !title.is_some_and(|title| title.is_match(&window.title)) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(wm_class: Option<&str>, process: Option<&str>, title: Option<&str>, action: RuleAction) -> AppRule {
+        AppRule {
+            wm_class: wm_class.map(str::to_string),
+            process: process.map(str::to_string),
+            title: title.map(str::to_string),
+            action,
+        }
+    }
+
+    fn window(instance: &str, class: &str, title: &str, process: Option<&str>) -> FocusedWindow {
+        FocusedWindow {
+            instance: instance.to_string(),
+            class: class.to_string(),
+            title: title.to_string(),
+            process: process.map(str::to_string),
+            ..FocusedWindow::default()
+        }
+    }
+
+    fn switch_to(profile: &str) -> RuleAction {
+        RuleAction::SwitchProfile { profile: profile.to_string() }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = RuleSet::new(&[
+            rule(Some("code"), None, Some("\\.md$"), switch_to("Writing")),
+            rule(Some("code"), None, None, RuleAction::Pause),
+            rule(None, None, Some("README"), switch_to("Docs")),
+        ]);
+
+        let readme = window("code", "Code", "README.md", None);
+        assert_eq!(rules.find(&readme).map(|rule| &rule.action), Some(&switch_to("Writing")));
+        let source = window("code", "Code", "main.rs", None);
+        assert_eq!(rules.find(&source).map(|rule| &rule.action), Some(&RuleAction::Pause));
+        let browser = window("firefox", "Firefox", "README - GitHub", None);
+        assert_eq!(rules.find(&browser).map(|rule| &rule.action), Some(&switch_to("Docs")));
+        assert_eq!(rules.find(&window("gimp", "Gimp", "Untitled", None)), None);
+    }
+
+    #[test]
+    fn wm_class_matches_instance_or_class_in_any_case() {
+        let rules = RuleSet::new(&[rule(Some("FIREFOX"), None, None, RuleAction::Pause)]);
+
+        assert!(rules.find(&window("firefox", "", "", None)).is_some());
+        assert!(rules.find(&window("Navigator", "Firefox", "", None)).is_some());
+        assert!(rules.find(&window("firefox-esr", "Firefox-esr", "", None)).is_none());
+    }
+
+    #[test]
+    fn title_is_a_pattern_searched_in_the_title() {
+        let rules = RuleSet::new(&[rule(None, None, Some("(?i)youtube|netflix"), RuleAction::Pause)]);
+
+        assert!(rules.find(&window("", "", "Trailer - YouTube — Mozilla Firefox", None)).is_some());
+        assert!(rules.find(&window("", "", "Netflix", None)).is_some());
+        assert!(rules.find(&window("", "", "You tube", None)).is_none());
+    }
+
+    #[test]
+    fn every_criterion_of_a_rule_must_hold() {
+        let rules = RuleSet::new(&[rule(Some("code"), Some("code"), Some("rs$"), RuleAction::Pause)]);
+
+        assert!(rules.find(&window("code", "Code", "main.rs", Some("code"))).is_some());
+        assert!(rules.find(&window("code", "Code", "main.rs", Some("code-insiders"))).is_none());
+        assert!(rules.find(&window("code", "Code", "main.rs", None)).is_none());
+        assert!(rules.find(&window("code", "Code", "notes.md", Some("code"))).is_none());
+    }
+
+    #[test]
+    fn invalid_title_patterns_match_nothing() {
+        let rules = RuleSet::new(&[
+            rule(None, None, Some("("), RuleAction::Pause),
+            rule(None, None, Some(""), switch_to("Fallback")),
+        ]);
+
+        let focused = window("code", "Code", "(", None);
+        assert_eq!(rules.find(&focused).map(|rule| &rule.action), Some(&switch_to("Fallback")));
+    }
+
+    #[test]
+    fn drops_rules_that_name_nothing_or_are_out_of_range() {
+        let mut rules = vec![
+            rule(Some("code"), None, None, RuleAction::Pause),
+            rule(None, None, None, RuleAction::Pause),
+            rule(None, None, Some("["), RuleAction::Pause),
+            rule(Some("mpv"), None, None, RuleAction::LowerIntensity { intensity: 1.5 }),
+        ];
+
+        assert!(validate(&rules).unwrap_err().starts_with("rules[1]: "));
+        let errors = remove_invalid(&mut rules);
+        assert_eq!(errors.len(), 3);
+        assert!(errors[2].starts_with("rules[3]: intensity 1.5"));
+        assert_eq!(rules, vec![rule(Some("code"), None, None, RuleAction::Pause)]);
+    }
+}
@@ -0,0 +1,263 @@
+// Allowed ranges of the numeric settings
+//
+// Out-of-range values can freeze or blank the simulation, so every write
+// path checks them: settings saved from the UI and imported profiles are
+// rejected, while a file loaded at startup is clamped so one bad value
+// doesn't cost the rest of the settings.
+
+use serde::Serialize;
+use std::fmt;
+use std::ops::RangeInclusive;
+use ts_rs::TS;
+
+use super::{Config, FluidConfig, Palette, Profile};
+
+/// A numeric setting outside its allowed range.
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct FieldError {
+    /// Path of the setting as named in the config file, e.g. `fluid.CURL`
+    pub field: String,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} is {}, expected {} to {}", self.field, self.value, self.min, self.max)
+    }
+}
+
+/// Why a config was refused, as the frontend receives it.
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[ts(export)]
+pub struct ConfigError {
+    pub message: String,
+    /// The settings out of range; empty when something else is wrong
+    pub fields: Vec<FieldError>,
+}
+
+impl From<String> for ConfigError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            fields: Vec::new(),
+        }
+    }
+}
+
+impl From<Vec<FieldError>> for ConfigError {
+    fn from(fields: Vec<FieldError>) -> Self {
+        Self {
+            message: format!("Invalid config: {}", describe(&fields)),
+            fields,
+        }
+    }
+}
+
+/// Joins `errors` into one line for logs and error messages.
+pub fn describe(errors: &[FieldError]) -> String {
+    errors.iter().map(FieldError::to_string).collect::<Vec<_>>().join("; ")
+}
+
+type Field<'a> = (&'static str, RangeInclusive<f64>, &'a mut f64);
+
+impl FluidConfig {
+    /// The ranges offered by the Settings panel.
+    fn fields(&mut self) -> [Field<'_>; 7] {
+        [
+            ("PRESSURE", 0.0..=1.0, &mut self.pressure),
+            ("CURL", 0.0..=30.0, &mut self.curl),
+            ("SPLAT_RADIUS", 0.05..=0.5, &mut self.splat_radius),
+            ("SPLAT_FORCE", 1000.0..=20000.0, &mut self.splat_force),
+            ("DENSITY_DISSIPATION", 0.1..=10.0, &mut self.density_dissipation),
+            ("VELOCITY_DISSIPATION", 0.1..=10.0, &mut self.velocity_dissipation),
+            ("COLOR_UPDATE_SPEED", 1.0..=50.0, &mut self.color_update_speed),
+        ]
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        into_result(self.clone().clamp())
+    }
+
+    /// Moves every value into its range, returning the ones that weren't.
+    pub fn clamp(&mut self) -> Vec<FieldError> {
+        clamp_fields(self.fields())
+    }
+}
+
+impl Palette {
+    fn fields(&mut self) -> [Field<'_>; 4] {
+        [
+            ("hueRange[0]", 0.0..=1.0, &mut self.hue_range.0),
+            ("hueRange[1]", 0.0..=1.0, &mut self.hue_range.1),
+            ("saturation", 0.0..=1.0, &mut self.saturation),
+            ("brightness", 0.0..=1.0, &mut self.brightness),
+        ]
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        into_result(self.clone().clamp())
+    }
+
+    pub fn clamp(&mut self) -> Vec<FieldError> {
+        clamp_fields(self.fields())
+    }
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let mut errors = prefixed("fluid", self.fluid.validate().err().unwrap_or_default());
+        errors.extend(prefixed("palette", self.palette.validate().err().unwrap_or_default()));
+        into_result(errors)
+    }
+
+    pub fn clamp(&mut self) -> Vec<FieldError> {
+        let mut errors = prefixed("fluid", self.fluid.clamp());
+        errors.extend(prefixed("palette", self.palette.clamp()));
+        errors
+    }
+}
+
+impl Config {
+    pub fn validate(&self) -> Result<(), Vec<FieldError>> {
+        let errors = self.profiles.iter()
+            .flat_map(|profile| {
+                prefixed(&format!("profiles[{}]", profile.name), profile.validate().err().unwrap_or_default())
+            })
+            .collect();
+        into_result(errors)
+    }
+
+    pub fn clamp(&mut self) -> Vec<FieldError> {
+        self.profiles.iter_mut()
+            .flat_map(|profile| prefixed(&format!("profiles[{}]", profile.name), profile.clamp()))
+            .collect()
+    }
+}
+
+fn clamp_fields<'a>(fields: impl IntoIterator<Item = Field<'a>>) -> Vec<FieldError> {
+    fields.into_iter()
+        .filter(|(_, range, value)| !range.contains(*value))
+        .map(|(field, range, value)| {
+            let error = FieldError {
+                field: field.to_string(),
+                value: *value,
+                min: *range.start(),
+                max: *range.end(),
+            };
+            // NaN has no nearest bound
+            *value = if value.is_nan() { *range.start() } else { value.clamp(*range.start(), *range.end()) };
+            error
+        })
+        .collect()
+}
+
+fn prefixed(prefix: &str, errors: Vec<FieldError>) -> Vec<FieldError> {
+    errors.into_iter()
+        .map(|error| FieldError {
+            field: format!("{}.{}", prefix, error.field),
+            ..error
+        })
+        .collect()
+}
+
+fn into_result(errors: Vec<FieldError>) -> Result<(), Vec<FieldError>> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(field: &str, value: f64, min: f64, max: f64) -> FieldError {
+        FieldError {
+            field: field.to_string(),
+            value,
+            min,
+            max,
+        }
+    }
+
+    #[test]
+    fn accepts_the_bounds_themselves() {
+        let fluid = FluidConfig {
+            pressure: 0.0,
+            curl: 30.0,
+            splat_radius: 0.05,
+            splat_force: 20000.0,
+            density_dissipation: 0.1,
+            velocity_dissipation: 10.0,
+            color_update_speed: 1.0,
+            shading: true,
+        };
+        assert_eq!(fluid.validate(), Ok(()));
+
+        let palette = Palette {
+            hue_range: (0.0, 1.0),
+            saturation: 0.0,
+            brightness: 1.0,
+        };
+        assert_eq!(palette.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_values_past_the_bounds() {
+        let fluid = FluidConfig {
+            curl: 30.5,
+            splat_force: 999.0,
+            ..FluidConfig::default()
+        };
+        assert_eq!(
+            fluid.validate(),
+            Err(vec![
+                error("CURL", 30.5, 0.0, 30.0),
+                error("SPLAT_FORCE", 999.0, 1000.0, 20000.0),
+            ])
+        );
+
+        let palette = Palette {
+            hue_range: (-0.1, 1.0),
+            ..Palette::default()
+        };
+        assert_eq!(palette.validate(), Err(vec![error("hueRange[0]", -0.1, 0.0, 1.0)]));
+    }
+
+    #[test]
+    fn clamps_to_the_nearest_bound() {
+        let mut fluid = FluidConfig {
+            pressure: 2.0,
+            splat_radius: 0.0,
+            color_update_speed: f64::NAN,
+            ..FluidConfig::default()
+        };
+        assert_eq!(fluid.clamp().len(), 3);
+        assert_eq!(fluid.pressure, 1.0);
+        assert_eq!(fluid.splat_radius, 0.05);
+        // NaN has no nearest bound and takes the lower one
+        assert_eq!(fluid.color_update_speed, 1.0);
+        assert_eq!(fluid.validate(), Ok(()));
+
+        let mut palette = Palette {
+            brightness: 1.5,
+            ..Palette::default()
+        };
+        assert_eq!(palette.clamp(), vec![error("brightness", 1.5, 0.0, 1.0)]);
+        assert_eq!(palette.brightness, 1.0);
+    }
+
+    #[test]
+    fn names_the_profile_of_each_error() {
+        let mut config = Config::default();
+        config.profiles[0].palette.saturation = 3.0;
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors, vec![error("profiles[Default].palette.saturation", 3.0, 0.0, 1.0)]);
+        assert_eq!(ConfigError::from(errors).fields.len(), 1);
+    }
+}
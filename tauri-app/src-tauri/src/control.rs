@@ -0,0 +1,290 @@
+// Control socket speaking JSON-RPC 2.0
+//
+// Lets scripts, window-manager keybindings and demo tooling drive the
+// overlay. Each line sent to `$XDG_RUNTIME_DIR/magic-cursor-control.sock`
+// is one request (or batch), and each answer is one line back:
+//
+//   {"jsonrpc": "2.0", "id": 1, "method": "switch_profile", "params": {"name": "Work"}}
+//   {"jsonrpc": "2.0", "id": 1, "result": null}
+//
+// The methods themselves are implemented by the handler passed to `serve`.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::Arc;
+use std::thread;
+
+use crate::socket;
+
+const SOCKET_NAME: &str = "magic-cursor-control.sock";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// The method ran and failed
+const SERVER_ERROR: i64 = -32000;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn method_not_found(method: &str) -> Self {
+        Self {
+            code: METHOD_NOT_FOUND,
+            message: format!("Unknown method '{}'", method),
+        }
+    }
+
+    pub fn invalid_params(message: String) -> Self {
+        Self {
+            code: INVALID_PARAMS,
+            message,
+        }
+    }
+}
+
+impl From<String> for RpcError {
+    fn from(message: String) -> Self {
+        Self {
+            code: SERVER_ERROR,
+            message,
+        }
+    }
+}
+
+/// Answers one call: `(method, params)` to a result or an error.
+pub type Handler = dyn Fn(&str, &Map<String, Value>) -> Result<Value, RpcError> + Send + Sync;
+
+/// Listens on the control socket, serving each client on its own thread.
+///
+/// Must only be called by the single running instance, as it replaces a
+/// socket file left behind by an earlier one.
+pub fn serve(handler: Arc<Handler>) -> Result<(), String> {
+    let path = socket::runtime_path(SOCKET_NAME)?;
+    let listener = match UnixListener::bind(&path) {
+        Err(e) if e.kind() == ErrorKind::AddrInUse => {
+            fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove stale {}: {}", path.display(), e))?;
+            UnixListener::bind(&path)
+        }
+        result => result,
+    }
+    .map_err(|e| format!("Failed to bind {}: {}", path.display(), e))?;
+    socket::restrict(&path)?;
+    println!("Control socket listening on {}", path.display());
+
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("Failed to accept control connection: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = socket::check_peer(&stream) {
+            eprintln!("{}", e);
+            continue;
+        }
+
+        let handler = handler.clone();
+        thread::spawn(move || {
+            if let Err(e) = serve_client(stream, &*handler) {
+                eprintln!("Control connection closed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Reads the named parameter `name`.
+pub fn param<T: DeserializeOwned>(params: &Map<String, Value>, name: &str) -> Result<T, RpcError> {
+    let value = params.get(name)
+        .ok_or_else(|| RpcError::invalid_params(format!("Missing parameter '{}'", name)))?;
+    serde_json::from_value(value.clone())
+        .map_err(|e| RpcError::invalid_params(format!("Invalid parameter '{}': {}", name, e)))
+}
+
+fn serve_client(stream: UnixStream, handler: &Handler) -> Result<(), String> {
+    let mut writer = &stream;
+    for line in BufReader::new(&stream).lines() {
+        let line = line.map_err(|e| format!("read failed: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(response) = answer(&line, handler) {
+            let mut text = response.to_string();
+            text.push('\n');
+            writer.write_all(text.as_bytes())
+                .map_err(|e| format!("write failed: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// The response to one line, or `None` when it only held notifications.
+fn answer(line: &str, handler: &Handler) -> Option<Value> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, format!("Parse error: {}", e))),
+    };
+
+    match request {
+        Value::Array(batch) if batch.is_empty() => {
+            Some(error_response(Value::Null, INVALID_REQUEST, "Empty batch".to_string()))
+        }
+        Value::Array(batch) => {
+            let responses: Vec<Value> = batch.into_iter()
+                .filter_map(|request| call(request, handler))
+                .collect();
+            (!responses.is_empty()).then_some(Value::Array(responses))
+        }
+        request => call(request, handler),
+    }
+}
+
+fn call(request: Value, handler: &Handler) -> Option<Value> {
+    let Value::Object(mut request) = request else {
+        return Some(error_response(Value::Null, INVALID_REQUEST, "Expected an object".to_string()));
+    };
+
+    // Requests without an id are notifications and get no response
+    let id = request.remove("id");
+    let response_id = id.clone().unwrap_or(Value::Null);
+
+    if request.get("jsonrpc").and_then(Value::as_str) != Some("2.0") {
+        return Some(error_response(response_id, INVALID_REQUEST, "Expected \"jsonrpc\": \"2.0\"".to_string()));
+    }
+    let Some(method) = request.get("method").and_then(Value::as_str) else {
+        return Some(error_response(response_id, INVALID_REQUEST, "Missing method".to_string()));
+    };
+    let params = match request.get("params") {
+        None => Map::new(),
+        Some(Value::Object(params)) => params.clone(),
+        Some(_) => {
+            return Some(error_response(response_id, INVALID_PARAMS, "Params must be an object".to_string()));
+        }
+    };
+
+    let result = handler(method, &params);
+    id.as_ref()?;
+
+    Some(match result {
+        Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": response_id, "result": result }),
+        Err(error) => serde_json::json!({ "jsonrpc": "2.0", "id": response_id, "error": error }),
+    })
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": RpcError { code, message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Echoes `params` for `echo`, fails `fail` and knows nothing else.
+    fn handler(method: &str, params: &Map<String, Value>) -> Result<Value, RpcError> {
+        match method {
+            "echo" => Ok(Value::Object(params.clone())),
+            "name" => param::<String>(params, "name").map(Value::from),
+            "fail" => Err("it broke".to_string().into()),
+            _ => Err(RpcError::method_not_found(method)),
+        }
+    }
+
+    fn answer_json(line: &str) -> Option<Value> {
+        answer(line, &handler)
+    }
+
+    fn error(id: Value, code: i64, message: &str) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+    }
+
+    #[test]
+    fn answers_a_request_with_its_id() {
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": "a", "method": "echo", "params": {"x": 1}}"#),
+            Some(json!({ "jsonrpc": "2.0", "id": "a", "result": { "x": 1 } }))
+        );
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": 2, "method": "echo"}"#),
+            Some(json!({ "jsonrpc": "2.0", "id": 2, "result": {} }))
+        );
+    }
+
+    #[test]
+    fn notifications_get_no_answer() {
+        assert_eq!(answer_json(r#"{"jsonrpc": "2.0", "method": "echo"}"#), None);
+        assert_eq!(answer_json(r#"{"jsonrpc": "2.0", "method": "fail"}"#), None);
+        assert_eq!(answer_json(r#"[{"jsonrpc": "2.0", "method": "echo"}]"#), None);
+    }
+
+    #[test]
+    fn reports_handler_errors() {
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": 1, "method": "fail"}"#),
+            Some(error(json!(1), SERVER_ERROR, "it broke"))
+        );
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": 1, "method": "nope"}"#),
+            Some(error(json!(1), METHOD_NOT_FOUND, "Unknown method 'nope'"))
+        );
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": 1, "method": "name"}"#),
+            Some(error(json!(1), INVALID_PARAMS, "Missing parameter 'name'"))
+        );
+        let wrong_type = answer_json(r#"{"jsonrpc": "2.0", "id": 1, "method": "name", "params": {"name": 3}}"#);
+        assert_eq!(wrong_type.unwrap()["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn rejects_malformed_requests() {
+        assert_eq!(answer_json("{").unwrap()["error"]["code"], PARSE_ERROR);
+        assert_eq!(answer_json("[]"), Some(error(Value::Null, INVALID_REQUEST, "Empty batch")));
+        assert_eq!(answer_json("3"), Some(error(Value::Null, INVALID_REQUEST, "Expected an object")));
+        assert_eq!(
+            answer_json(r#"{"id": 1, "method": "echo"}"#),
+            Some(error(json!(1), INVALID_REQUEST, "Expected \"jsonrpc\": \"2.0\""))
+        );
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": 1}"#),
+            Some(error(json!(1), INVALID_REQUEST, "Missing method"))
+        );
+        assert_eq!(
+            answer_json(r#"{"jsonrpc": "2.0", "id": 1, "method": "echo", "params": [1]}"#),
+            Some(error(json!(1), INVALID_PARAMS, "Params must be an object"))
+        );
+    }
+
+    #[test]
+    fn answers_batches_in_order() {
+        let batch = r#"[
+            {"jsonrpc": "2.0", "id": 1, "method": "echo"},
+            {"jsonrpc": "2.0", "method": "echo"},
+            5,
+            {"jsonrpc": "2.0", "id": 2, "method": "fail"}
+        ]"#;
+        assert_eq!(
+            answer_json(&batch.replace('\n', " ")),
+            Some(json!([
+                { "jsonrpc": "2.0", "id": 1, "result": {} },
+                error(Value::Null, INVALID_REQUEST, "Expected an object"),
+                error(json!(2), SERVER_ERROR, "it broke"),
+            ]))
+        );
+    }
+}
@@ -0,0 +1,431 @@
+// KDE Plasma's compositor, KWin, keeps the pointer position to itself too,
+// but runs scripts that can call out over D-Bus. We load one that reports
+// every cursor move and layout change to an object we serve on the session
+// bus.
+
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use zbus::blocking::connection::Builder;
+use zbus::blocking::{Connection, Proxy};
+use zbus::{fdo, interface};
+
+use super::wayland::{physical_size, to_physical, LayoutMonitor};
+use super::{Capabilities, CursorSource, Waker};
+
+const TRACKER_PATH: &str = "/com/magiccursor/CursorTracker";
+
+/// Reports to `@SERVICE@`, replaced by our unique bus name. Needs KWin 6.
+const SCRIPT: &str = r#"
+function call(method, argument) {
+    callDBus("@SERVICE@", "/com/magiccursor/CursorTracker", "com.magiccursor.CursorTracker", method, argument);
+}
+function layout() {
+    call("Layout", JSON.stringify(workspace.screens.map(function (screen) {
+        var geometry = screen.geometry;
+        return { x: geometry.x, y: geometry.y, width: geometry.width, height: geometry.height, scale: screen.devicePixelRatio };
+    })));
+}
+function moved() {
+    call("Moved", Math.round(workspace.cursorPos.x) + "," + Math.round(workspace.cursorPos.y));
+}
+workspace.screensChanged.connect(layout);
+workspace.cursorPosChanged.connect(moved);
+layout();
+moved();
+"#;
+
+/// How long KWin gets to run the script and report a first position.
+const START_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Numbers the scripts of this process, as a replacement source is loaded
+/// before the one it replaces is dropped.
+static NEXT_SCRIPT: AtomicU32 = AtomicU32::new(0);
+
+/// What the script last reported, shared with the D-Bus object.
+#[derive(Default)]
+struct Reports {
+    /// Logical position
+    position: Option<(i32, i32)>,
+    screens: Vec<LayoutMonitor>,
+    /// A move since the last `wait`
+    moved: bool,
+    woken: bool,
+}
+
+#[derive(Default)]
+struct Shared {
+    reports: Mutex<Reports>,
+    changed: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> Result<MutexGuard<'_, Reports>, String> {
+        self.reports.lock().map_err(|e| format!("Failed to lock KWin reports: {}", e))
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Reports)) {
+        if let Ok(mut reports) = self.reports.lock() {
+            change(&mut reports);
+            self.changed.notify_all();
+        }
+    }
+}
+
+struct Tracker {
+    shared: Arc<Shared>,
+}
+
+#[interface(name = "com.magiccursor.CursorTracker")]
+impl Tracker {
+    /// `x,y` in logical coordinates
+    fn moved(&self, position: &str) -> fdo::Result<()> {
+        let position = parse_position(position).map_err(fdo::Error::InvalidArgs)?;
+        self.shared.update(|reports| {
+            reports.position = Some(position);
+            reports.moved = true;
+        });
+        Ok(())
+    }
+
+    /// JSON list of `{x, y, width, height, scale}`, in logical coordinates
+    fn layout(&self, screens: &str) -> fdo::Result<()> {
+        let screens = parse_screens(screens).map_err(fdo::Error::InvalidArgs)?;
+        self.shared.update(|reports| reports.screens = screens);
+        Ok(())
+    }
+}
+
+/// Pointer source fed by a KWin script.
+///
+/// The script stays loaded while the source lives and is unloaded on drop.
+pub struct KWinPointer {
+    connection: Connection,
+    shared: Arc<Shared>,
+    plugin_name: String,
+    script_path: PathBuf,
+}
+
+impl KWinPointer {
+    /// Loads the script into the KWin of this session.
+    pub fn connect() -> Result<Self, String> {
+        Self::connect_to(Builder::session())
+    }
+
+    /// Like `connect`, on the bus `bus` connects to.
+    fn connect_to(bus: zbus::Result<Builder<'_>>) -> Result<Self, String> {
+        let shared = Arc::new(Shared::default());
+        let connection = bus
+            .and_then(|builder| builder.serve_at(TRACKER_PATH, Tracker { shared: shared.clone() }))
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("Failed to connect to the session bus: {}", e))?;
+        let service = connection.unique_name()
+            .ok_or_else(|| "The session bus gave us no name".to_string())?
+            .to_string();
+
+        let plugin_name = format!("magic-cursor-{}-{}", process::id(), NEXT_SCRIPT.fetch_add(1, Ordering::Relaxed));
+        let script_path = env::temp_dir().join(format!("{}.js", plugin_name));
+        fs::write(&script_path, SCRIPT.replace("@SERVICE@", &service))
+            .map_err(|e| format!("Failed to write KWin script {}: {}", script_path.display(), e))?;
+
+        // From here on, dropping the pointer cleans up
+        let pointer = Self {
+            connection,
+            shared,
+            plugin_name,
+            script_path,
+        };
+        let id: i32 = pointer.scripting()?
+            .call("loadScript", &(pointer.script_path.to_string_lossy().as_ref(), pointer.plugin_name.as_str()))
+            .map_err(|e| format!("KWin is not running or has no scripting: {}", e))?;
+        if id < 0 {
+            return Err("KWin refused to load the cursor script".to_string());
+        }
+        pointer.scripting()?
+            .call::<_, _, ()>("start", &())
+            .map_err(|e| format!("Failed to start the KWin script: {}", e))?;
+
+        pointer.wait_for_reports(START_TIMEOUT)?;
+        Ok(pointer)
+    }
+
+    fn scripting(&self) -> Result<Proxy<'_>, String> {
+        Proxy::new(&self.connection, "org.kde.KWin", "/Scripting", "org.kde.kwin.Scripting")
+            .map_err(|e| format!("Failed to reach KWin scripting: {}", e))
+    }
+
+    /// Blocks until the script has sent both a layout and a position.
+    fn wait_for_reports(&self, timeout: Duration) -> Result<(), String> {
+        let deadline = Instant::now() + timeout;
+        let mut reports = self.shared.lock()?;
+        while reports.position.is_none() || reports.screens.is_empty() {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                return Err("KWin ran the cursor script but it never reported back; KWin 6 is required".to_string());
+            }
+            reports = self.shared.changed.wait_timeout(reports, left)
+                .map_err(|e| format!("Failed to lock KWin reports: {}", e))?
+                .0;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for KWinPointer {
+    fn drop(&mut self) {
+        if let Ok(scripting) = self.scripting() {
+            let _ = scripting.call::<_, _, bool>("unloadScript", &(self.plugin_name.as_str(),));
+        }
+        let _ = fs::remove_file(&self.script_path);
+    }
+}
+
+impl CursorSource for KWinPointer {
+    fn name(&self) -> &'static str {
+        "kwin"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            event_driven: true,
+            buttons: false,
+            limitation: None,
+        }
+    }
+
+    /// Bounding box of the screens KWin last reported, in physical pixels.
+    fn screen_size(&mut self) -> Result<(i32, i32), String> {
+        physical_size(&self.shared.lock()?.screens)
+    }
+
+    fn position(&mut self) -> Result<(i32, i32), String> {
+        let reports = self.shared.lock()?;
+        let (x, y) = reports.position.ok_or_else(|| "KWin has not reported the cursor yet".to_string())?;
+        Ok(to_physical(&reports.screens, x, y))
+    }
+
+    fn wait(&mut self) -> Result<(), String> {
+        let mut reports = self.shared.lock()?;
+        while !reports.moved && !reports.woken {
+            reports = self.shared.changed.wait(reports)
+                .map_err(|e| format!("Failed to lock KWin reports: {}", e))?;
+        }
+        reports.moved = false;
+        reports.woken = false;
+        Ok(())
+    }
+
+    fn waker(&self) -> Option<Waker> {
+        let shared = self.shared.clone();
+        Some(Arc::new(move || shared.update(|reports| reports.woken = true)))
+    }
+}
+
+fn parse_position(position: &str) -> Result<(i32, i32), String> {
+    let parse = |value: &str| value.trim().parse::<i32>()
+        .map_err(|e| format!("Invalid cursor coordinate '{}': {}", value.trim(), e));
+    let (x, y) = position.split_once(',')
+        .ok_or_else(|| format!("Unexpected cursor position '{}'", position))?;
+    Ok((parse(x)?, parse(y)?))
+}
+
+/// A screen as the script reports it.
+#[derive(Deserialize)]
+struct Screen {
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    scale: f64,
+}
+
+fn parse_screens(screens: &str) -> Result<Vec<LayoutMonitor>, String> {
+    let screens: Vec<Screen> = serde_json::from_str(screens)
+        .map_err(|e| format!("Invalid screen list from KWin: {}", e))?;
+
+    Ok(screens.into_iter()
+        .map(|screen| {
+            let scale = if screen.scale > 0.0 { screen.scale } else { 1.0 };
+            let physical = |size: i32| (size as f64 * scale).round() as i32;
+            LayoutMonitor {
+                x: screen.x,
+                y: screen.y,
+                width: physical(screen.width),
+                height: physical(screen.height),
+                scale,
+            }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::thread;
+
+    /// A private bus, stopped when dropped.
+    struct Daemon {
+        process: Child,
+        address: String,
+    }
+
+    impl Daemon {
+        /// `None` when `dbus-daemon` is not installed.
+        fn start() -> Option<Self> {
+            let mut process = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(process.stdout.as_mut().unwrap()).read_line(&mut address).unwrap();
+            Some(Self {
+                process,
+                address: address.trim().to_string(),
+            })
+        }
+
+        fn connect(&self) -> zbus::Result<Builder<'_>> {
+            Builder::address(self.address.as_str())
+        }
+    }
+
+    impl Drop for Daemon {
+        fn drop(&mut self) {
+            let _ = self.process.kill();
+            let _ = self.process.wait();
+        }
+    }
+
+    /// What the fake KWin was asked to do.
+    #[derive(Default)]
+    struct Calls {
+        loaded: Vec<String>,
+        unloaded: Vec<String>,
+        /// Where the loaded script reports to
+        service: Option<String>,
+    }
+
+    /// Stands in for KWin: "running" the script reports a 4K screen at
+    /// scale 2 with a 1080p one to its right, and the cursor on the first.
+    struct FakeKWin {
+        calls: Arc<Mutex<Calls>>,
+        client: Connection,
+    }
+
+    #[interface(name = "org.kde.kwin.Scripting")]
+    impl FakeKWin {
+        #[zbus(name = "loadScript")]
+        fn load_script(&self, file_path: &str, plugin_name: &str) -> i32 {
+            let script = fs::read_to_string(file_path).unwrap();
+            let service = script.split("callDBus(\"").nth(1).and_then(|rest| rest.split('"').next()).unwrap();
+            let mut calls = self.calls.lock().unwrap();
+            calls.service = Some(service.to_string());
+            calls.loaded.push(plugin_name.to_string());
+            0
+        }
+
+        #[zbus(name = "start")]
+        fn start(&self) {
+            let service = self.calls.lock().unwrap().service.clone().unwrap();
+            let client = self.client.clone();
+            // Calling back from a method of our own would block its reply
+            thread::spawn(move || {
+                let screens = r#"[{"x":0,"y":0,"width":1920,"height":1080,"scale":2},{"x":1920,"y":0,"width":1920,"height":1080,"scale":1}]"#;
+                report(&client, &service, "Layout", screens);
+                report(&client, &service, "Moved", "100,50");
+            });
+        }
+
+        #[zbus(name = "unloadScript")]
+        fn unload_script(&self, plugin_name: &str) -> bool {
+            self.calls.lock().unwrap().unloaded.push(plugin_name.to_string());
+            true
+        }
+    }
+
+    fn report(client: &Connection, service: &str, method: &str, argument: &str) {
+        client.call_method(Some(service), TRACKER_PATH, Some("com.magiccursor.CursorTracker"), method, &(argument,))
+            .unwrap();
+    }
+
+    /// Starts a fake KWin on `daemon`, returning its connection and calls.
+    fn fake_kwin(daemon: &Daemon) -> (Connection, Arc<Mutex<Calls>>) {
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        let fake = FakeKWin {
+            calls: calls.clone(),
+            client: daemon.connect().unwrap().build().unwrap(),
+        };
+        let kwin = daemon.connect()
+            .and_then(|builder| builder.name("org.kde.KWin"))
+            .and_then(|builder| builder.serve_at("/Scripting", fake))
+            .and_then(|builder| builder.build())
+            .unwrap();
+        (kwin, calls)
+    }
+
+    #[test]
+    fn parses_the_script_reports() {
+        assert_eq!(parse_position("12,-40"), Ok((12, -40)));
+        assert!(parse_position("12").is_err());
+        assert!(parse_position("a,b").is_err());
+
+        let screens = parse_screens(r#"[{"x":0,"y":0,"width":1280,"height":720,"scale":1.5}]"#).unwrap();
+        assert_eq!(screens, [LayoutMonitor { x: 0, y: 0, width: 1920, height: 1080, scale: 1.5 }]);
+        assert!(parse_screens("{}").is_err());
+    }
+
+    #[test]
+    fn follows_the_cursor_through_a_script() {
+        let Some(daemon) = Daemon::start() else {
+            eprintln!("dbus-daemon is not installed, skipping");
+            return;
+        };
+        let (_kwin, calls) = fake_kwin(&daemon);
+
+        let mut pointer = KWinPointer::connect_to(daemon.connect()).unwrap();
+        assert_eq!(pointer.screen_size(), Ok((3840, 2160)));
+        assert_eq!(pointer.position(), Ok((200, 100)));
+
+        // Each move ends a wait
+        pointer.wait().unwrap();
+        let client = daemon.connect().unwrap().build().unwrap();
+        let service = calls.lock().unwrap().service.clone().unwrap();
+        report(&client, &service, "Moved", "2000,10");
+        pointer.wait().unwrap();
+        assert_eq!(pointer.position(), Ok((2000, 10)));
+
+        let waker = pointer.waker().unwrap();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            waker();
+        });
+        pointer.wait().unwrap();
+
+        let plugin_name = pointer.plugin_name.clone();
+        let script_path = pointer.script_path.clone();
+        drop(pointer);
+        let calls = calls.lock().unwrap();
+        assert_eq!(calls.loaded, calls.unloaded);
+        assert_eq!(calls.unloaded, [plugin_name]);
+        assert!(!script_path.exists());
+    }
+
+    #[test]
+    fn fails_without_kwin() {
+        let Some(daemon) = Daemon::start() else {
+            eprintln!("dbus-daemon is not installed, skipping");
+            return;
+        };
+        let error = KWinPointer::connect_to(daemon.connect()).err().unwrap();
+        assert!(error.starts_with("KWin is not running"), "{}", error);
+    }
+}
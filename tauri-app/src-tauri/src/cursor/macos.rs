@@ -0,0 +1,49 @@
+use cocoa::appkit::{NSEvent, NSScreen};
+use cocoa::base::{id, nil};
+use cocoa::foundation::{NSPoint, NSRect};
+
+use super::{Capabilities, CursorSource};
+
+/// Polls the cursor position through AppKit.
+///
+/// AppKit works in points; positions are converted to physical pixels with
+/// the main screen's backing scale factor.
+pub struct CocoaPointer;
+
+impl CocoaPointer {
+    fn main_screen() -> Result<(NSRect, f64), String> {
+        unsafe {
+            let screen: id = NSScreen::mainScreen(nil);
+            if screen == nil {
+                return Err("No main screen available".to_string());
+            }
+            Ok((NSScreen::frame(screen), NSScreen::backingScaleFactor(screen)))
+        }
+    }
+}
+
+impl CursorSource for CocoaPointer {
+    fn name(&self) -> &'static str {
+        "cocoa"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            event_driven: false,
+            buttons: false,
+            limitation: None,
+        }
+    }
+
+    fn screen_size(&mut self) -> Result<(i32, i32), String> {
+        let (frame, scale) = Self::main_screen()?;
+        Ok(((frame.size.width * scale) as i32, (frame.size.height * scale) as i32))
+    }
+
+    fn position(&mut self) -> Result<(i32, i32), String> {
+        let (frame, scale) = Self::main_screen()?;
+        // AppKit's origin is the bottom-left corner of the main screen
+        let location: NSPoint = unsafe { NSEvent::mouseLocation(nil) };
+        Ok(((location.x * scale) as i32, ((frame.size.height - location.y) * scale) as i32))
+    }
+}
@@ -0,0 +1,586 @@
+// Native pointer backends and the shared driver loop used by the global mouse
+// tracking thread
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use ts_rs::TS;
+
+#[cfg(target_os = "linux")]
+pub mod kwin;
+#[cfg(target_os = "macos")]
+pub mod macos;
+#[cfg(target_os = "linux")]
+pub mod wayland;
+#[cfg(target_os = "windows")]
+pub mod windows;
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+/// Delay between samples for sources that have to be polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(16);
+/// Delay between checks while tracking is switched off.
+const PAUSED_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What a cursor source can do.
+#[derive(Clone, Copy, Debug)]
+pub struct Capabilities {
+    /// `wait` blocks until the pointer moves instead of sleeping a fixed interval
+    pub event_driven: bool,
+    /// Button and wheel input is reported through `take_input`
+    pub buttons: bool,
+    /// Why positions are only partially accurate, if they are
+    pub limitation: Option<&'static str>,
+}
+
+/// Cuts a source's blocking `wait` short, from any thread.
+pub type Waker = Arc<dyn Fn() + Send + Sync>;
+
+/// A platform backend able to report the global pointer position.
+pub trait CursorSource: Send {
+    /// Short identifier reported in `TrackingStatus::backend`.
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Size of the area positions are reported in, in physical pixels.
+    fn screen_size(&mut self) -> Result<(i32, i32), String>;
+
+    /// Current pointer position.
+    fn position(&mut self) -> Result<(i32, i32), String>;
+
+    /// Blocks until a new sample should be taken.
+    ///
+    /// Polling sources keep the default fixed interval; subscribing sources
+    /// override this to return only once the pointer has moved. An error
+    /// means the source is gone for good.
+    fn wait(&mut self) -> Result<(), String> {
+        thread::sleep(POLL_INTERVAL);
+        Ok(())
+    }
+
+    /// Button and wheel input received since the last call.
+    fn take_input(&mut self) -> Vec<PointerInput> {
+        Vec::new()
+    }
+
+    /// Makes `wait` return early, for sources that block in it until the
+    /// pointer moves.
+    fn waker(&self) -> Option<Waker> {
+        None
+    }
+}
+
+/// One pointer position, as forwarded by the driver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CursorSample {
+    pub x: i32,
+    pub y: i32,
+    pub screen_width: i32,
+    pub screen_height: i32,
+}
+
+/// Keyboard modifiers held while a button or wheel event happened.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, TS)]
+#[ts(export)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub control: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+/// Button or wheel input observed without grabbing the pointer.
+///
+/// Buttons use DOM `MouseEvent.button` numbering: 0 primary, 1 middle,
+/// 2 secondary, 3 back, 4 forward. Wheel deltas are in notches, positive
+/// for down and right like DOM `WheelEvent`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PointerInput {
+    Button {
+        button: u8,
+        pressed: bool,
+        x: i32,
+        y: i32,
+        modifiers: Modifiers,
+    },
+    Scroll {
+        delta_x: i32,
+        delta_y: i32,
+        x: i32,
+        y: i32,
+        modifiers: Modifiers,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export)]
+pub enum TrackingState {
+    /// Backend selection has not finished yet
+    Starting,
+    /// Cursor positions are accurate everywhere on screen
+    Active,
+    /// Tracking works, but only partially (e.g. XWayland-only coordinates)
+    Degraded,
+    /// No usable backend; `global-mouse-move` will never fire
+    Unavailable,
+}
+
+/// Which pointer backend is in use and how well it works.
+///
+/// Sent with `tracking-status-changed` and returned by `get_tracking_status`.
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TrackingStatus {
+    pub backend: String,
+    pub state: TrackingState,
+    pub message: Option<String>,
+}
+
+impl TrackingStatus {
+    pub fn new(backend: &str, state: TrackingState, message: Option<String>) -> Self {
+        Self {
+            backend: backend.to_string(),
+            state,
+            message,
+        }
+    }
+
+    fn for_source(source: &dyn CursorSource) -> Self {
+        match source.capabilities().limitation {
+            Some(limitation) => Self::new(source.name(), TrackingState::Degraded, Some(limitation.to_string())),
+            None => Self::new(source.name(), TrackingState::Active, None),
+        }
+    }
+}
+
+impl Default for TrackingStatus {
+    fn default() -> Self {
+        Self::new("none", TrackingState::Starting, None)
+    }
+}
+
+/// Picks the best cursor source available on this machine.
+pub fn default_source() -> Result<Box<dyn CursorSource>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let is_wayland = wayland::is_wayland_session();
+        if is_wayland {
+            match wayland::HyprlandPointer::connect() {
+                Ok(pointer) => return Ok(Box::new(pointer)),
+                Err(e) => println!("Hyprland cursor backend unavailable: {}", e),
+            }
+            match kwin::KWinPointer::connect() {
+                Ok(pointer) => return Ok(Box::new(pointer)),
+                Err(e) => println!("KWin cursor backend unavailable: {}", e),
+            }
+        }
+
+        let mut pointer = x11::X11Pointer::connect(None).map_err(|e| {
+            if is_wayland {
+                format!("No supported Wayland cursor backend found and XWayland is unavailable ({})", e)
+            } else {
+                e
+            }
+        })?;
+
+        // Wake up only when the pointer moves; fall back to polling on
+        // servers without XInput2
+        if let Err(e) = pointer.select_raw_events() {
+            eprintln!("Warning: {}. Falling back to polling.", e);
+        }
+
+        Ok(Box::new(pointer))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Ok(Box::new(windows::Win32Pointer))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Ok(Box::new(macos::CocoaPointer))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+    {
+        Err("Global mouse tracking is not supported on this platform".to_string())
+    }
+}
+
+/// Runs a cursor source and forwards its samples while tracking is enabled.
+///
+/// Owns the handling of `AppState.mouse_tracking`; sources are handed over
+/// from any thread with `replace_source`, so backends can be swapped at
+/// runtime.
+pub struct CursorDriver {
+    tracking_enabled: Arc<Mutex<bool>>,
+    replacement: Mutex<Option<Box<dyn CursorSource>>>,
+    /// Interrupts the active source, so a replacement isn't kept waiting
+    waker: Mutex<Option<Waker>>,
+    screen_changed: AtomicBool,
+    paused: AtomicBool,
+}
+
+impl CursorDriver {
+    pub fn new(tracking_enabled: Arc<Mutex<bool>>) -> Self {
+        Self {
+            tracking_enabled,
+            replacement: Mutex::new(None),
+            waker: Mutex::new(None),
+            screen_changed: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+        }
+    }
+
+    /// Stops forwarding samples until resumed, independently of
+    /// click-through.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Makes the driver re-read the screen size before its next sample.
+    pub fn refresh_screen(&self) {
+        self.screen_changed.store(true, Ordering::Relaxed);
+    }
+
+    /// Hands a source to the driver, which switches to it right away, even
+    /// if the active source is waiting for the pointer to move.
+    pub fn replace_source(&self, source: Box<dyn CursorSource>) -> Result<(), String> {
+        let mut replacement = self.replacement.lock()
+            .map_err(|e| format!("Failed to lock cursor source: {}", e))?;
+        *replacement = Some(source);
+        drop(replacement);
+
+        if let Some(wake) = self.waker.lock().ok().and_then(|waker| waker.clone()) {
+            wake();
+        }
+        Ok(())
+    }
+
+    /// Drives the queued source, then whichever source replaces it.
+    ///
+    /// Never returns. When a source fails the driver idles until a new one
+    /// is handed over. `on_status` is called whenever the active source
+    /// changes or fails, `on_move` for every new pointer position and
+    /// `on_input` for every button or wheel event.
+    pub fn run<S, M, I>(&self, mut on_status: S, mut on_move: M, mut on_input: I)
+    where
+        S: FnMut(TrackingStatus),
+        M: FnMut(CursorSample),
+        I: FnMut(PointerInput),
+    {
+        let mut source: Option<Box<dyn CursorSource>> = None;
+        let mut screen = (0, 0);
+        let mut last_position = None;
+
+        loop {
+            if let Some(mut next) = self.take_replacement() {
+                on_status(TrackingStatus::for_source(&*next));
+                screen = read_screen_size(&mut *next);
+                self.set_waker(next.waker());
+                source = Some(next);
+                last_position = None;
+            }
+
+            let Some(active) = source.as_mut() else {
+                thread::sleep(PAUSED_INTERVAL);
+                continue;
+            };
+
+            if self.screen_changed.swap(false, Ordering::Relaxed) {
+                screen = read_screen_size(&mut **active);
+            }
+
+            if let Err(e) = active.wait() {
+                on_status(TrackingStatus::new(active.name(), TrackingState::Unavailable, Some(e)));
+                self.set_waker(None);
+                source = None;
+                continue;
+            }
+
+            // Drain input even while paused so stale clicks aren't replayed
+            let input = if active.capabilities().buttons {
+                active.take_input()
+            } else {
+                Vec::new()
+            };
+
+            let is_tracking = match self.tracking_enabled.lock() {
+                Ok(guard) => *guard && !self.is_paused(),
+                Err(_) => {
+                    thread::sleep(PAUSED_INTERVAL);
+                    continue;
+                }
+            };
+
+            if !is_tracking {
+                if !active.capabilities().event_driven {
+                    thread::sleep(PAUSED_INTERVAL);
+                }
+                continue;
+            }
+
+            for event in input {
+                on_input(event);
+            }
+
+            if let Ok((x, y)) = active.position() {
+                if last_position == Some((x, y)) {
+                    continue;
+                }
+                last_position = Some((x, y));
+
+                on_move(CursorSample {
+                    x,
+                    y,
+                    screen_width: screen.0,
+                    screen_height: screen.1,
+                });
+            }
+        }
+    }
+
+    fn take_replacement(&self) -> Option<Box<dyn CursorSource>> {
+        self.replacement.lock().ok().and_then(|mut replacement| replacement.take())
+    }
+
+    fn set_waker(&self, waker: Option<Waker>) {
+        if let Ok(mut current) = self.waker.lock() {
+            *current = waker;
+        }
+    }
+}
+
+fn read_screen_size(source: &mut dyn CursorSource) -> (i32, i32) {
+    source.screen_size().unwrap_or_else(|e| {
+        eprintln!("Warning: {}. Assuming 1920x1080.", e);
+        (1920, 1080)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    /// One `wait` of a `MockSource`.
+    struct Step {
+        position: (i32, i32),
+        input: Vec<PointerInput>,
+    }
+
+    fn at(x: i32, y: i32) -> Step {
+        Step { position: (x, y), input: Vec::new() }
+    }
+
+    /// Replays scripted steps, then fails or blocks until woken.
+    struct MockSource {
+        name: &'static str,
+        limitation: Option<&'static str>,
+        steps: VecDeque<Step>,
+        position: (i32, i32),
+        input: Vec<PointerInput>,
+        /// Set to keep waiting after the last step instead of failing
+        wake: Option<(Sender<()>, Receiver<()>)>,
+    }
+
+    impl MockSource {
+        fn new(name: &'static str, steps: Vec<Step>) -> Self {
+            Self {
+                name,
+                limitation: None,
+                steps: steps.into(),
+                position: (0, 0),
+                input: Vec::new(),
+                wake: None,
+            }
+        }
+
+        fn blocking(mut self) -> Self {
+            self.wake = Some(mpsc::channel());
+            self
+        }
+    }
+
+    impl CursorSource for MockSource {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                event_driven: true,
+                buttons: true,
+                limitation: self.limitation,
+            }
+        }
+
+        fn screen_size(&mut self) -> Result<(i32, i32), String> {
+            Ok((800, 600))
+        }
+
+        fn position(&mut self) -> Result<(i32, i32), String> {
+            Ok(self.position)
+        }
+
+        fn wait(&mut self) -> Result<(), String> {
+            if let Some(step) = self.steps.pop_front() {
+                self.position = step.position;
+                self.input.extend(step.input);
+                return Ok(());
+            }
+            match &self.wake {
+                Some((_, woken)) => woken.recv().map_err(|e| e.to_string()),
+                None => Err("script finished".to_string()),
+            }
+        }
+
+        fn take_input(&mut self) -> Vec<PointerInput> {
+            std::mem::take(&mut self.input)
+        }
+
+        fn waker(&self) -> Option<Waker> {
+            let (wake, _) = self.wake.as_ref()?;
+            let wake = wake.clone();
+            Some(Arc::new(move || {
+                let _ = wake.send(());
+            }))
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Seen {
+        Status(String, TrackingState),
+        Move(i32, i32),
+        Input(PointerInput),
+    }
+
+    /// Runs `driver` on its own thread, which outlives the test.
+    fn start(driver: &Arc<CursorDriver>) -> Receiver<Seen> {
+        let (seen, receiver) = mpsc::channel();
+        let (on_status, on_move, on_input) = (seen.clone(), seen.clone(), seen);
+        let driver = driver.clone();
+        thread::spawn(move || {
+            driver.run(
+                move |status| {
+                    let _ = on_status.send(Seen::Status(status.backend, status.state));
+                },
+                move |sample| {
+                    let _ = on_move.send(Seen::Move(sample.x, sample.y));
+                },
+                move |input| {
+                    let _ = on_input.send(Seen::Input(input));
+                },
+            )
+        });
+        receiver
+    }
+
+    fn next(seen: &Receiver<Seen>) -> Seen {
+        seen.recv_timeout(Duration::from_secs(5)).expect("driver went quiet")
+    }
+
+    fn driver_with(source: MockSource) -> Arc<CursorDriver> {
+        let driver = Arc::new(CursorDriver::new(Arc::new(Mutex::new(true))));
+        driver.replace_source(Box::new(source)).unwrap();
+        driver
+    }
+
+    #[test]
+    fn forwards_each_new_position_once() {
+        let driver = driver_with(MockSource::new("mock", vec![at(1, 1), at(1, 1), at(2, 2), at(1, 1)]));
+        let seen = start(&driver);
+
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Move(1, 1));
+        assert_eq!(next(&seen), Seen::Move(2, 2));
+        assert_eq!(next(&seen), Seen::Move(1, 1));
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Unavailable));
+    }
+
+    #[test]
+    fn forwards_button_input_before_the_position() {
+        let press = PointerInput::Button {
+            button: 0,
+            pressed: true,
+            x: 5,
+            y: 6,
+            modifiers: Modifiers::default(),
+        };
+        let step = Step { position: (5, 6), input: vec![press] };
+        let driver = driver_with(MockSource::new("mock", vec![step]));
+        let seen = start(&driver);
+
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Input(press));
+        assert_eq!(next(&seen), Seen::Move(5, 6));
+    }
+
+    #[test]
+    fn drops_samples_and_input_while_paused() {
+        let step = Step {
+            position: (5, 6),
+            input: vec![PointerInput::Scroll {
+                delta_x: 0,
+                delta_y: 1,
+                x: 5,
+                y: 6,
+                modifiers: Modifiers::default(),
+            }],
+        };
+        let driver = driver_with(MockSource::new("mock", vec![step, at(7, 8)]));
+        driver.set_paused(true);
+        let seen = start(&driver);
+
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Unavailable));
+    }
+
+    #[test]
+    fn reports_limitations_as_degraded() {
+        let mut source = MockSource::new("mock", Vec::new());
+        source.limitation = Some("partial");
+        let driver = driver_with(source);
+        let seen = start(&driver);
+
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Degraded));
+        assert_eq!(next(&seen), Seen::Status("mock".into(), TrackingState::Unavailable));
+    }
+
+    #[test]
+    fn switches_to_a_replacement_while_the_source_waits() {
+        let driver = driver_with(MockSource::new("first", vec![at(1, 1)]).blocking());
+        let seen = start(&driver);
+        assert_eq!(next(&seen), Seen::Status("first".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Move(1, 1));
+
+        // `first` now blocks for good unless the driver wakes it
+        driver.replace_source(Box::new(MockSource::new("second", vec![at(2, 2)]))).unwrap();
+        assert_eq!(next(&seen), Seen::Status("second".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Move(2, 2));
+        assert_eq!(next(&seen), Seen::Status("second".into(), TrackingState::Unavailable));
+    }
+
+    #[test]
+    fn picks_up_a_source_after_the_last_one_failed() {
+        let driver = driver_with(MockSource::new("first", Vec::new()));
+        let seen = start(&driver);
+        assert_eq!(next(&seen), Seen::Status("first".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Status("first".into(), TrackingState::Unavailable));
+
+        driver.replace_source(Box::new(MockSource::new("second", vec![at(3, 4)]))).unwrap();
+        assert_eq!(next(&seen), Seen::Status("second".into(), TrackingState::Active));
+        assert_eq!(next(&seen), Seen::Move(3, 4));
+    }
+}
@@ -0,0 +1,209 @@
+// Wayland has no core protocol for reading the global pointer position, so
+// tracking relies on compositor IPC where the compositor offers it. This is
+// Hyprland's; KWin's is in `kwin.rs`. GNOME falls back to XWayland.
+
+use std::env;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::{Capabilities, CursorSource};
+
+/// Returns true when the app is running inside a Wayland session.
+pub fn is_wayland_session() -> bool {
+    env::var_os("WAYLAND_DISPLAY").is_some()
+        || env::var("XDG_SESSION_TYPE").map(|t| t == "wayland").unwrap_or(false)
+}
+
+/// Pointer source backed by Hyprland's command socket.
+///
+/// Hyprland answers one request per connection and then closes it, so each
+/// sample connects anew; the socket is local and that costs little.
+pub struct HyprlandPointer {
+    socket_path: PathBuf,
+    /// Read by `screen_size`, to turn logical positions into physical ones
+    monitors: Vec<LayoutMonitor>,
+}
+
+impl HyprlandPointer {
+    /// Locates the command socket of the running Hyprland instance.
+    pub fn connect() -> Result<Self, String> {
+        let signature = env::var("HYPRLAND_INSTANCE_SIGNATURE")
+            .map_err(|_| "Hyprland is not running".to_string())?;
+
+        // Hyprland 0.40 moved its sockets from /tmp into the runtime dir
+        let mut candidates = Vec::new();
+        if let Some(runtime_dir) = env::var_os("XDG_RUNTIME_DIR") {
+            candidates.push(PathBuf::from(runtime_dir).join("hypr").join(&signature));
+        }
+        candidates.push(PathBuf::from("/tmp/hypr").join(&signature));
+
+        let socket_path = candidates.into_iter()
+            .map(|dir| dir.join(".socket.sock"))
+            .find(|path| path.exists())
+            .ok_or_else(|| "Hyprland command socket not found".to_string())?;
+
+        let pointer = Self {
+            socket_path,
+            monitors: Vec::new(),
+        };
+        pointer.query_pointer()?;
+        Ok(pointer)
+    }
+
+    /// Current pointer position in logical layout coordinates.
+    pub fn query_pointer(&self) -> Result<(i32, i32), String> {
+        let reply = self.request("cursorpos")?;
+        let (x, y) = reply.trim().split_once(',')
+            .ok_or_else(|| format!("Unexpected cursorpos reply: {}", reply.trim()))?;
+
+        let parse = |value: &str| value.trim().parse::<i32>()
+            .map_err(|e| format!("Invalid cursor coordinate '{}': {}", value.trim(), e));
+        Ok((parse(x)?, parse(y)?))
+    }
+
+    fn request(&self, command: &str) -> Result<String, String> {
+        let mut stream = UnixStream::connect(&self.socket_path)
+            .map_err(|e| format!("Failed to connect to Hyprland: {}", e))?;
+        stream.set_read_timeout(Some(Duration::from_millis(250)))
+            .map_err(|e| format!("Failed to configure Hyprland socket: {}", e))?;
+
+        stream.write_all(command.as_bytes())
+            .map_err(|e| format!("Failed to send '{}' to Hyprland: {}", command, e))?;
+
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)
+            .map_err(|e| format!("Failed to read Hyprland reply: {}", e))?;
+        Ok(reply)
+    }
+}
+
+impl CursorSource for HyprlandPointer {
+    fn name(&self) -> &'static str {
+        "hyprland"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            event_driven: false,
+            buttons: false,
+            limitation: None,
+        }
+    }
+
+    /// Bounding box of all monitors, each scaled by its own factor as the
+    /// window system reports them to the overlay.
+    fn screen_size(&mut self) -> Result<(i32, i32), String> {
+        let reply = self.request("j/monitors")?;
+        let monitors: Vec<serde_json::Value> = serde_json::from_str(&reply)
+            .map_err(|e| format!("Invalid monitor list from Hyprland: {}", e))?;
+        self.monitors = monitors.iter().map(LayoutMonitor::parse).collect();
+        physical_size(&self.monitors)
+    }
+
+    /// `cursorpos` scaled by the factor of the monitor under it.
+    fn position(&mut self) -> Result<(i32, i32), String> {
+        let (x, y) = self.query_pointer()?;
+        Ok(to_physical(&self.monitors, x, y))
+    }
+}
+
+/// A monitor as a compositor lists it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct LayoutMonitor {
+    /// Position in the logical layout
+    pub x: i32,
+    pub y: i32,
+    /// Size of the mode in physical pixels, after rotation
+    pub width: i32,
+    pub height: i32,
+    pub scale: f64,
+}
+
+impl LayoutMonitor {
+    /// Reads an entry of Hyprland's `j/monitors`.
+    fn parse(monitor: &serde_json::Value) -> Self {
+        let field = |key: &str| monitor[key].as_i64().unwrap_or(0) as i32;
+        // Odd transforms rotate by 90 or 270 degrees
+        let (width, height) = if field("transform") % 2 == 1 {
+            (field("height"), field("width"))
+        } else {
+            (field("width"), field("height"))
+        };
+
+        Self {
+            x: field("x"),
+            y: field("y"),
+            width,
+            height,
+            scale: monitor["scale"].as_f64().filter(|&scale| scale > 0.0).unwrap_or(1.0),
+        }
+    }
+
+    /// True when the logical position `(x, y)` is on this monitor.
+    fn contains(&self, x: i32, y: i32) -> bool {
+        let logical = |size: i32| (size as f64 / self.scale).round() as i32;
+        x >= self.x && y >= self.y && x < self.x + logical(self.width) && y < self.y + logical(self.height)
+    }
+
+    fn scaled(&self, value: i32) -> i32 {
+        (value as f64 * self.scale).round() as i32
+    }
+}
+
+/// Bounding box of `monitors` in physical pixels.
+pub(super) fn physical_size(monitors: &[LayoutMonitor]) -> Result<(i32, i32), String> {
+    let width = monitors.iter().map(|monitor| monitor.scaled(monitor.x) + monitor.width).max().unwrap_or(0);
+    let height = monitors.iter().map(|monitor| monitor.scaled(monitor.y) + monitor.height).max().unwrap_or(0);
+    if width <= 0 || height <= 0 {
+        return Err("The compositor reported no monitors".to_string());
+    }
+    Ok((width, height))
+}
+
+/// Turns a logical position into physical pixels. Positions off every
+/// monitor are left as they are.
+pub(super) fn to_physical(monitors: &[LayoutMonitor], x: i32, y: i32) -> (i32, i32) {
+    match monitors.iter().find(|monitor| monitor.contains(x, y)) {
+        Some(monitor) => (monitor.scaled(x), monitor.scaled(y)),
+        None => (x, y),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// A 4K monitor at scale 2 with a 1080p one turned on its side to
+    /// its right.
+    fn monitors() -> Vec<LayoutMonitor> {
+        [
+            json!({ "x": 0, "y": 0, "width": 3840, "height": 2160, "scale": 2.0, "transform": 0 }),
+            json!({ "x": 1920, "y": 0, "width": 1920, "height": 1080, "scale": 1.0, "transform": 1 }),
+        ].iter().map(LayoutMonitor::parse).collect()
+    }
+
+    #[test]
+    fn monitors_are_turned_by_their_transform() {
+        let side = monitors()[1];
+        assert_eq!((side.width, side.height), (1080, 1920));
+        assert!(side.contains(2999, 1919));
+        assert!(!side.contains(3000, 0));
+    }
+
+    #[test]
+    fn size_and_positions_are_physical() {
+        let monitors = monitors();
+        assert_eq!(physical_size(&monitors), Ok((3840, 2160)));
+        assert_eq!(to_physical(&monitors, 960, 540), (1920, 1080));
+        assert_eq!(to_physical(&monitors, 2000, 100), (2000, 100));
+        assert_eq!(to_physical(&monitors, 5000, 100), (5000, 100));
+    }
+
+    #[test]
+    fn physical_size_needs_a_monitor() {
+        assert!(physical_size(&[]).is_err());
+    }
+}
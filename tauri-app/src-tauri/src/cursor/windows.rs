@@ -0,0 +1,34 @@
+use windows::Win32::Foundation::POINT;
+use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
+
+use super::{Capabilities, CursorSource};
+
+/// Polls the cursor position through the Win32 API.
+pub struct Win32Pointer;
+
+impl CursorSource for Win32Pointer {
+    fn name(&self) -> &'static str {
+        "win32"
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            event_driven: false,
+            buttons: false,
+            limitation: None,
+        }
+    }
+
+    fn screen_size(&mut self) -> Result<(i32, i32), String> {
+        unsafe { Ok((GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN))) }
+    }
+
+    fn position(&mut self) -> Result<(i32, i32), String> {
+        let mut point = POINT { x: 0, y: 0 };
+        unsafe {
+            GetCursorPos(&mut point)
+                .map_err(|e| format!("GetCursorPos failed: {}", e))?;
+        }
+        Ok((point.x, point.y))
+    }
+}
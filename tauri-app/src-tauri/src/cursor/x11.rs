@@ -0,0 +1,282 @@
+use rustix::event::{poll, PollFd, PollFlags};
+use rustix::io::Errno;
+use rustix::pipe::{pipe_with, PipeFlags};
+use std::os::fd::OwnedFd;
+use std::sync::Arc;
+use x11rb::connection::Connection;
+use x11rb::protocol::xinput::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::{ConnectionExt as _, KeyButMask, QueryPointerReply, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+use super::{wayland, Capabilities, CursorSource, Modifiers, PointerInput, Waker};
+
+/// Direct connection to the X server used to sample the global pointer.
+///
+/// Talks the X11 protocol over the socket named by `DISPLAY`, so it works
+/// against any server (including Xvfb) without external binaries.
+pub struct X11Pointer {
+    conn: RustConnection,
+    root: Window,
+    raw_events: bool,
+    xwayland: bool,
+    pending_input: Vec<PointerInput>,
+    /// Self-pipe whose write end cuts `wait_for_input` short
+    wake: Option<(OwnedFd, Arc<OwnedFd>)>,
+}
+
+impl X11Pointer {
+    /// Connects to `display`, or to `$DISPLAY` when `None`.
+    pub fn connect(display: Option<&str>) -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(display)
+            .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+
+        let root = conn.setup().roots[screen_num].root;
+
+        Ok(Self {
+            conn,
+            root,
+            raw_events: false,
+            xwayland: wayland::is_wayland_session(),
+            pending_input: Vec::new(),
+            wake: None,
+        })
+    }
+
+    /// Current pointer position relative to the root window.
+    pub fn query_pointer(&self) -> Result<(i32, i32), String> {
+        let reply = self.query_pointer_state()?;
+        Ok((reply.root_x as i32, reply.root_y as i32))
+    }
+
+    fn query_pointer_state(&self) -> Result<QueryPointerReply, String> {
+        self.conn.query_pointer(self.root)
+            .map_err(|e| format!("Failed to send QueryPointer: {}", e))?
+            .reply()
+            .map_err(|e| format!("QueryPointer failed: {}", e))
+    }
+
+    /// Subscribes to XInput2 raw motion and button events from every master
+    /// pointer.
+    ///
+    /// Raw events are delivered on the root window regardless of which
+    /// client the pointer is over, without grabbing the device, so the
+    /// application under the cursor still receives its input.
+    ///
+    /// Needs XInput 2.1: before it, raw events stop while a device is
+    /// grabbed, which includes the implicit grab of every button drag.
+    pub fn select_raw_events(&mut self) -> Result<(), String> {
+        let version = self.conn.xinput_xi_query_version(2, 2)
+            .map_err(|e| format!("Failed to send XIQueryVersion: {}", e))?
+            .reply()
+            .map_err(|e| format!("XInput2 is not available: {}", e))?;
+
+        if (version.major_version, version.minor_version) < (2, 1) {
+            return Err(format!(
+                "XInput {}.{} does not support raw events",
+                version.major_version, version.minor_version
+            ));
+        }
+
+        let mask = xinput::EventMask {
+            deviceid: xinput::Device::ALL_MASTER.into(),
+            mask: vec![
+                xinput::XIEventMask::RAW_MOTION
+                    | xinput::XIEventMask::RAW_BUTTON_PRESS
+                    | xinput::XIEventMask::RAW_BUTTON_RELEASE,
+            ],
+        };
+
+        self.conn.xinput_xi_select_events(self.root, &[mask])
+            .map_err(|e| format!("Failed to send XISelectEvents: {}", e))?
+            .check()
+            .map_err(|e| format!("XISelectEvents failed: {}", e))?;
+
+        let (read, write) = pipe_with(PipeFlags::CLOEXEC | PipeFlags::NONBLOCK)
+            .map_err(|e| format!("Failed to create wake-up pipe: {}", e))?;
+        self.wake = Some((read, Arc::new(write)));
+        self.raw_events = true;
+        Ok(())
+    }
+
+    /// Blocks until the next raw motion or button event arrives, or the
+    /// source's waker is called.
+    ///
+    /// Button events are queued for `take_input`. Requires a prior call to
+    /// `select_raw_events`; unrelated events are discarded.
+    pub fn wait_for_input(&mut self) -> Result<(), String> {
+        loop {
+            let event = self.conn.poll_for_event()
+                .map_err(|e| format!("Lost connection to X server: {}", e))?;
+            let Some(event) = event else {
+                if self.sleep()? {
+                    return Ok(());
+                }
+                continue;
+            };
+
+            match event {
+                Event::XinputRawMotion(_) => return Ok(()),
+                Event::XinputRawButtonPress(event) => {
+                    self.queue_button(event.detail, true);
+                    return Ok(());
+                }
+                Event::XinputRawButtonRelease(event) => {
+                    self.queue_button(event.detail, false);
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Sleeps until the server sends something or the waker is called,
+    /// returning true in the latter case.
+    fn sleep(&self) -> Result<bool, String> {
+        let Some((wake, _)) = &self.wake else {
+            return Err("Raw events are not selected".to_string());
+        };
+        self.conn.flush()
+            .map_err(|e| format!("Lost connection to X server: {}", e))?;
+
+        let mut fds = [
+            PollFd::new(self.conn.stream(), PollFlags::IN),
+            PollFd::new(wake, PollFlags::IN),
+        ];
+        match poll(&mut fds, None) {
+            Ok(_) | Err(Errno::INTR) => {}
+            Err(e) => return Err(format!("Failed to wait for X events: {}", e)),
+        }
+        if fds[1].revents().is_empty() {
+            return Ok(false);
+        }
+
+        // Several wake-ups in a row count as one
+        let mut buffer = [0; 64];
+        while matches!(rustix::io::read(wake, &mut buffer), Ok(n) if n > 0) {}
+        Ok(true)
+    }
+
+    fn queue_button(&mut self, detail: u32, pressed: bool) {
+        // Raw events carry neither coordinates nor modifier state
+        let Ok(state) = self.query_pointer_state() else {
+            return;
+        };
+        let (x, y) = (state.root_x as i32, state.root_y as i32);
+        let modifiers = modifiers_from_mask(state.mask);
+
+        // Core protocol numbering: 1-3 left/middle/right, 4-7 wheel
+        // up/down/left/right, 8-9 back/forward
+        let (delta_x, delta_y) = match detail {
+            4 => (0, -1),
+            5 => (0, 1),
+            6 => (-1, 0),
+            7 => (1, 0),
+            _ => (0, 0),
+        };
+        if delta_x != 0 || delta_y != 0 {
+            // Each wheel notch is a press/release pair; report it once
+            if pressed {
+                self.pending_input.push(PointerInput::Scroll { delta_x, delta_y, x, y, modifiers });
+            }
+            return;
+        }
+
+        let button = match detail {
+            1 => 0,
+            2 => 1,
+            3 => 2,
+            8 => 3,
+            9 => 4,
+            _ => return,
+        };
+        self.pending_input.push(PointerInput::Button { button, pressed, x, y, modifiers });
+    }
+}
+
+fn modifiers_from_mask(mask: KeyButMask) -> Modifiers {
+    Modifiers {
+        shift: mask.contains(KeyButMask::SHIFT),
+        control: mask.contains(KeyButMask::CONTROL),
+        alt: mask.contains(KeyButMask::MOD1),
+        meta: mask.contains(KeyButMask::MOD4),
+    }
+}
+
+impl CursorSource for X11Pointer {
+    fn name(&self) -> &'static str {
+        if self.raw_events { "x11-xinput2" } else { "x11-poll" }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            event_driven: self.raw_events,
+            buttons: self.raw_events,
+            limitation: if self.xwayland {
+                Some("Running under XWayland: the cursor is only tracked over X11 windows. \
+                      Native Wayland tracking needs Hyprland or KDE Plasma 6")
+            } else {
+                None
+            },
+        }
+    }
+
+    fn screen_size(&mut self) -> Result<(i32, i32), String> {
+        // The root window grows and shrinks with the monitor layout
+        let geometry = self.conn.get_geometry(self.root)
+            .map_err(|e| format!("Failed to send GetGeometry: {}", e))?
+            .reply()
+            .map_err(|e| format!("GetGeometry failed: {}", e))?;
+
+        Ok((geometry.width as i32, geometry.height as i32))
+    }
+
+    fn position(&mut self) -> Result<(i32, i32), String> {
+        self.query_pointer()
+    }
+
+    fn wait(&mut self) -> Result<(), String> {
+        if self.raw_events {
+            self.wait_for_input()
+        } else {
+            std::thread::sleep(super::POLL_INTERVAL);
+            Ok(())
+        }
+    }
+
+    fn take_input(&mut self) -> Vec<PointerInput> {
+        std::mem::take(&mut self.pending_input)
+    }
+
+    fn waker(&self) -> Option<Waker> {
+        let (_, write) = self.wake.as_ref()?;
+        let write = write.clone();
+        // A full pipe already holds a pending wake-up
+        Some(Arc::new(move || {
+            let _ = rustix::io::write(&*write, &[0]);
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Needs an X server, e.g. `xvfb-run cargo test`; skipped without one.
+    #[test]
+    fn query_pointer_returns_warped_position() {
+        if std::env::var_os("DISPLAY").is_none() {
+            eprintln!("DISPLAY is not set, skipping");
+            return;
+        }
+        let pointer = X11Pointer::connect(None).unwrap();
+
+        for (x, y) in [(123, 45), (7, 300)] {
+            pointer.conn.warp_pointer(x11rb::NONE, pointer.root, 0, 0, 0, 0, x, y)
+                .unwrap()
+                .check()
+                .unwrap();
+            assert_eq!(pointer.query_pointer().unwrap(), (x as i32, y as i32));
+        }
+    }
+}
@@ -0,0 +1,417 @@
+// D-Bus service `com.magiccursor.Overlay` on the session bus
+//
+// Another way in to the control methods (see `control.rs`): each D-Bus method
+// runs the matching control method, and the properties are read from
+// `get_state`. Call `Service::refresh` whenever that state may have changed
+// so clients get a PropertiesChanged signal.
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+
+use zbus::blocking::connection::Builder;
+use zbus::blocking::Connection;
+use zbus::object_server::SignalEmitter;
+use zbus::{fdo, interface, zvariant};
+
+use crate::control::Handler;
+
+pub const BUS_NAME: &str = "com.magiccursor.Overlay";
+pub const OBJECT_PATH: &str = "/com/magiccursor/Overlay";
+const INTERFACE_NAME: &str = "com.magiccursor.Overlay";
+
+/// The `get_state` fields published as properties.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    visible: bool,
+    fullscreen: bool,
+    click_through: bool,
+    tracking_paused: bool,
+    tracking: Tracking,
+    active_profile: String,
+    profiles: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+struct Tracking {
+    backend: String,
+    state: String,
+}
+
+impl Snapshot {
+    fn read(handler: &Handler) -> Result<Self, String> {
+        let state = handler("get_state", &Map::new()).map_err(|e| e.message)?;
+        serde_json::from_value(state).map_err(|e| format!("Unexpected state: {}", e))
+    }
+
+    /// The properties whose values differ from `previous`.
+    fn changes(&self, previous: &Snapshot) -> HashMap<&'static str, zvariant::Value<'static>> {
+        let mut changes = HashMap::new();
+        let mut add = |name, changed: bool, value: zvariant::Value<'static>| {
+            if changed {
+                changes.insert(name, value);
+            }
+        };
+
+        add("Visible", self.visible != previous.visible, self.visible.into());
+        add("Fullscreen", self.fullscreen != previous.fullscreen, self.fullscreen.into());
+        add("ClickThrough", self.click_through != previous.click_through, self.click_through.into());
+        add("TrackingPaused", self.tracking_paused != previous.tracking_paused, self.tracking_paused.into());
+        add("TrackingBackend", self.tracking.backend != previous.tracking.backend, self.tracking.backend.clone().into());
+        add("TrackingState", self.tracking.state != previous.tracking.state, self.tracking.state.clone().into());
+        add("ActiveProfile", self.active_profile != previous.active_profile, self.active_profile.clone().into());
+        add("Profiles", self.profiles != previous.profiles, self.profiles.clone().into());
+        changes
+    }
+}
+
+struct Overlay {
+    handler: Arc<Handler>,
+}
+
+impl Overlay {
+    fn call(&self, method: &str, params: Map<String, Value>) -> fdo::Result<()> {
+        (self.handler)(method, &params)
+            .map(|_| ())
+            .map_err(|e| fdo::Error::Failed(e.message))
+    }
+
+    fn snapshot(&self) -> fdo::Result<Snapshot> {
+        Snapshot::read(&*self.handler).map_err(fdo::Error::Failed)
+    }
+}
+
+#[interface(name = "com.magiccursor.Overlay")]
+impl Overlay {
+    fn show(&self) -> fdo::Result<()> {
+        self.call("show", Map::new())
+    }
+
+    fn hide(&self) -> fdo::Result<()> {
+        self.call("hide", Map::new())
+    }
+
+    fn set_visible(&self, visible: bool) -> fdo::Result<()> {
+        self.call(if visible { "show" } else { "hide" }, Map::new())
+    }
+
+    fn set_click_through(&self, enabled: bool) -> fdo::Result<()> {
+        self.call("set_click_through", Map::from_iter([("enabled".to_string(), Value::from(enabled))]))
+    }
+
+    fn toggle_click_through(&self) -> fdo::Result<()> {
+        self.call("toggle_click_through", Map::new())
+    }
+
+    fn toggle_fullscreen(&self) -> fdo::Result<()> {
+        self.call("toggle_fullscreen", Map::new())
+    }
+
+    fn switch_profile(&self, name: String) -> fdo::Result<()> {
+        self.call("switch_profile", Map::from_iter([("name".to_string(), Value::from(name))]))
+    }
+
+    fn pause_tracking(&self) -> fdo::Result<()> {
+        self.call("pause_tracking", Map::new())
+    }
+
+    fn resume_tracking(&self) -> fdo::Result<()> {
+        self.call("resume_tracking", Map::new())
+    }
+
+    #[zbus(property)]
+    fn visible(&self) -> fdo::Result<bool> {
+        Ok(self.snapshot()?.visible)
+    }
+
+    #[zbus(property)]
+    fn fullscreen(&self) -> fdo::Result<bool> {
+        Ok(self.snapshot()?.fullscreen)
+    }
+
+    #[zbus(property)]
+    fn click_through(&self) -> fdo::Result<bool> {
+        Ok(self.snapshot()?.click_through)
+    }
+
+    #[zbus(property)]
+    fn tracking_paused(&self) -> fdo::Result<bool> {
+        Ok(self.snapshot()?.tracking_paused)
+    }
+
+    /// Name of the mouse tracking backend, e.g. `x11-xinput2` or `hyprland`
+    #[zbus(property)]
+    fn tracking_backend(&self) -> fdo::Result<String> {
+        Ok(self.snapshot()?.tracking.backend)
+    }
+
+    /// `starting`, `active`, `degraded` or `unavailable`
+    #[zbus(property)]
+    fn tracking_state(&self) -> fdo::Result<String> {
+        Ok(self.snapshot()?.tracking.state)
+    }
+
+    #[zbus(property)]
+    fn active_profile(&self) -> fdo::Result<String> {
+        Ok(self.snapshot()?.active_profile)
+    }
+
+    #[zbus(property)]
+    fn profiles(&self) -> fdo::Result<Vec<String>> {
+        Ok(self.snapshot()?.profiles)
+    }
+}
+
+/// The registered service. Dropping it leaves the bus.
+pub struct Service {
+    refresh: Sender<()>,
+}
+
+/// Takes the bus name and serves the overlay object at `OBJECT_PATH`.
+///
+/// Must only be called by the single running instance.
+pub fn start(handler: Arc<Handler>) -> Result<Service, String> {
+    serve(Builder::session(), handler)
+}
+
+/// Like `start`, on the bus `bus` connects to.
+fn serve(bus: zbus::Result<Builder<'_>>, handler: Arc<Handler>) -> Result<Service, String> {
+    let connection = bus
+        .and_then(|builder| builder.name(BUS_NAME))
+        .and_then(|builder| builder.serve_at(OBJECT_PATH, Overlay { handler: handler.clone() }))
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Failed to register {} on the session bus: {}", BUS_NAME, e))?;
+    println!("D-Bus service {} registered", BUS_NAME);
+
+    // Signals go out from their own thread, so `refresh` can be called from
+    // anywhere, including while a D-Bus method is running
+    let (refresh, requests) = mpsc::channel();
+    let mut published = Snapshot::read(&*handler).unwrap_or_default();
+    thread::spawn(move || {
+        while requests.recv().is_ok() {
+            while requests.try_recv().is_ok() {}
+            match Snapshot::read(&*handler) {
+                Ok(snapshot) => {
+                    if let Err(e) = publish(&connection, snapshot.changes(&published)) {
+                        eprintln!("Failed to emit D-Bus property changes: {}", e);
+                    }
+                    published = snapshot;
+                }
+                Err(e) => eprintln!("Failed to read state for D-Bus: {}", e),
+            }
+        }
+    });
+
+    Ok(Service { refresh })
+}
+
+impl Service {
+    /// Emits PropertiesChanged for the properties that changed since last time.
+    pub fn refresh(&self) {
+        let _ = self.refresh.send(());
+    }
+}
+
+fn publish(connection: &Connection, changes: HashMap<&str, zvariant::Value<'_>>) -> zbus::Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let emitter = SignalEmitter::new(connection.inner(), OBJECT_PATH)?;
+    zbus::block_on(fdo::Properties::properties_changed(
+        &emitter,
+        INTERFACE_NAME.try_into()?,
+        changes,
+        Cow::Borrowed(&[]),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command, Stdio};
+    use std::sync::Mutex;
+    use zbus::blocking::{fdo::PropertiesProxy, proxy};
+    use zbus::proxy::CacheProperties;
+
+    use crate::control::RpcError;
+
+    /// A private bus, stopped when dropped.
+    struct Daemon {
+        process: Child,
+        address: String,
+    }
+
+    impl Daemon {
+        /// `None` when `dbus-daemon` is not installed.
+        fn start() -> Option<Self> {
+            let mut process = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            BufReader::new(process.stdout.as_mut().unwrap()).read_line(&mut address).unwrap();
+            Some(Self {
+                process,
+                address: address.trim().to_string(),
+            })
+        }
+    }
+
+    impl Drop for Daemon {
+        fn drop(&mut self) {
+            let _ = self.process.kill();
+            let _ = self.process.wait();
+        }
+    }
+
+    /// Stands in for the app's control methods.
+    fn handler(state: Arc<Mutex<Value>>) -> Arc<Handler> {
+        Arc::new(move |method: &str, params: &Map<String, Value>| {
+            let mut state = state.lock().unwrap();
+            match method {
+                "get_state" => return Ok(state.clone()),
+                "show" => state["visible"] = true.into(),
+                "hide" => state["visible"] = false.into(),
+                "set_click_through" => {
+                    state["clickThrough"] = crate::control::param::<bool>(params, "enabled")?.into();
+                }
+                "switch_profile" => return Err(RpcError::from("Profile 'Nope' does not exist".to_string())),
+                _ => return Err(RpcError::method_not_found(method)),
+            }
+            Ok(Value::Null)
+        })
+    }
+
+    fn initial_state() -> Arc<Mutex<Value>> {
+        Arc::new(Mutex::new(serde_json::json!({
+            "visible": true,
+            "fullscreen": false,
+            "clickThrough": false,
+            "trackingPaused": false,
+            "tracking": { "backend": "x11-xinput2", "state": "active" },
+            "activeProfile": "Default",
+            "profiles": ["Default"],
+        })))
+    }
+
+    fn connect(daemon: &Daemon) -> Connection {
+        zbus::blocking::connection::Builder::address(daemon.address.as_str())
+            .unwrap()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn changes_lists_only_the_differing_properties() {
+        let previous = Snapshot::default();
+        assert!(previous.changes(&previous).is_empty());
+
+        let snapshot = Snapshot {
+            visible: true,
+            tracking: Tracking {
+                backend: "hyprland".to_string(),
+                state: String::new(),
+            },
+            profiles: vec!["Default".to_string()],
+            ..Snapshot::default()
+        };
+        let changes = snapshot.changes(&previous);
+        let mut names: Vec<&str> = changes.keys().copied().collect();
+        names.sort();
+        assert_eq!(names, ["Profiles", "TrackingBackend", "Visible"]);
+        assert_eq!(changes["Visible"], zvariant::Value::from(true));
+        assert_eq!(changes["TrackingBackend"], zvariant::Value::from("hyprland"));
+    }
+
+    #[test]
+    fn serves_methods_and_properties_on_a_private_bus() {
+        let Some(daemon) = Daemon::start() else {
+            eprintln!("dbus-daemon is not installed, skipping");
+            return;
+        };
+        let state = initial_state();
+        let _service = serve(Builder::address(daemon.address.as_str()), handler(state.clone())).unwrap();
+
+        let client = connect(&daemon);
+        let overlay: zbus::blocking::Proxy = proxy::Builder::new(&client)
+            .destination(BUS_NAME)
+            .unwrap()
+            .path(OBJECT_PATH)
+            .unwrap()
+            .interface(INTERFACE_NAME)
+            .unwrap()
+            .cache_properties(CacheProperties::No)
+            .build()
+            .unwrap();
+
+        overlay.call_method("SetVisible", &(false,)).unwrap();
+        assert_eq!(state.lock().unwrap()["visible"], false);
+        assert!(!overlay.get_property::<bool>("Visible").unwrap());
+
+        overlay.call_method("SetClickThrough", &(true,)).unwrap();
+        assert!(overlay.get_property::<bool>("ClickThrough").unwrap());
+
+        assert_eq!(overlay.get_property::<String>("TrackingBackend").unwrap(), "x11-xinput2");
+        assert_eq!(overlay.get_property::<Vec<String>>("Profiles").unwrap(), vec!["Default"]);
+
+        let error = overlay.call_method("SwitchProfile", &("Nope",)).unwrap_err();
+        assert!(error.to_string().contains("Profile 'Nope' does not exist"), "{}", error);
+    }
+
+    #[test]
+    fn refresh_signals_the_changed_properties() {
+        let Some(daemon) = Daemon::start() else {
+            eprintln!("dbus-daemon is not installed, skipping");
+            return;
+        };
+        let state = initial_state();
+        let service = serve(Builder::address(daemon.address.as_str()), handler(state.clone())).unwrap();
+
+        let client = connect(&daemon);
+        let properties = PropertiesProxy::builder(&client)
+            .destination(BUS_NAME)
+            .unwrap()
+            .path(OBJECT_PATH)
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut signals = properties.receive_properties_changed().unwrap();
+        let mut next_changes = || {
+            let signal = signals.next().unwrap();
+            let args = signal.args().unwrap();
+            assert_eq!(args.interface_name().as_str(), INTERFACE_NAME);
+            let mut changed: Vec<(String, zvariant::OwnedValue)> = args.changed_properties().iter()
+                .map(|(name, value)| (name.to_string(), value.try_to_owned().unwrap()))
+                .collect();
+            changed.sort_by(|a, b| a.0.cmp(&b.0));
+            changed
+        };
+
+        {
+            let mut state = state.lock().unwrap();
+            state["visible"] = false.into();
+            state["activeProfile"] = "Calm".into();
+        }
+        service.refresh();
+        assert_eq!(next_changes(), vec![
+            ("ActiveProfile".to_string(), zvariant::Value::from("Calm").try_to_owned().unwrap()),
+            ("Visible".to_string(), zvariant::Value::from(false).try_to_owned().unwrap()),
+        ]);
+
+        // Nothing changed, so nothing is sent before the next real change
+        service.refresh();
+        state.lock().unwrap()["trackingPaused"] = true.into();
+        service.refresh();
+        assert_eq!(next_changes(), vec![
+            ("TrackingPaused".to_string(), zvariant::Value::from(true).try_to_owned().unwrap()),
+        ]);
+    }
+}
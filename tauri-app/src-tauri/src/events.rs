@@ -0,0 +1,269 @@
+// Payloads of the events emitted to the overlay webviews
+//
+// Coordinate convention: every position is relative to the top-left corner of
+// the monitor covered by the receiving overlay window. `x`, `y` and the
+// screen size are physical pixels as reported by the display server. The
+// `logical*` fields are the same values divided by `scaleFactor`, which makes
+// them CSS pixels in the overlay webview; the frontend should use those.
+//
+// The TypeScript definitions in `src/bindings` are generated from these types
+// by `cargo test`; regenerate them whenever a payload changes.
+
+use serde::Serialize;
+use ts_rs::TS;
+
+use crate::config::Config;
+use crate::cursor::{Modifiers, TrackingStatus};
+use crate::monitors::Monitor;
+
+/// Version of the payload layout, sent as `version` with every event.
+///
+/// Bump it whenever a field is renamed, removed or changes meaning.
+pub const SCHEMA_VERSION: u32 = 1;
+
+pub const MOUSE_MOVE: &str = "global-mouse-move";
+pub const MOUSE_DOWN: &str = "global-mouse-down";
+pub const MOUSE_UP: &str = "global-mouse-up";
+pub const SCROLL: &str = "global-scroll";
+pub const CLICK_THROUGH_CHANGED: &str = "click-through-changed";
+pub const TRACKING_STATUS_CHANGED: &str = "tracking-status-changed";
+pub const MONITORS_CHANGED: &str = "monitors-changed";
+pub const CONFIG_CHANGED: &str = "config-changed";
+pub const CONFIG_WARNING: &str = "config-warning";
+pub const INTENSITY_CHANGED: &str = "intensity-changed";
+pub const OPEN_COLOR_PRESETS: &str = "open-color-presets";
+pub const OPEN_THEME_CUSTOMIZER: &str = "open-theme-customizer";
+pub const OPEN_SETTINGS: &str = "open-settings";
+pub const OPEN_WELCOME: &str = "open-welcome";
+pub const REQUEST_OVERLAY_PERMISSION: &str = "request-overlay-permission";
+
+/// Pointer position inside an overlay window.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct OverlayPoint {
+    pub x: i32,
+    pub y: i32,
+    pub logical_x: f64,
+    pub logical_y: f64,
+    /// Physical pixels per CSS pixel on the overlay's monitor
+    pub scale_factor: f64,
+}
+
+impl OverlayPoint {
+    pub fn new(x: i32, y: i32, scale_factor: f64) -> Self {
+        Self {
+            x,
+            y,
+            logical_x: x as f64 / scale_factor,
+            logical_y: y as f64 / scale_factor,
+            scale_factor,
+        }
+    }
+}
+
+/// Payload of `global-mouse-move`.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MouseMovePayload {
+    pub version: u32,
+    #[serde(flatten)]
+    pub point: OverlayPoint,
+    pub screen_width: i32,
+    pub screen_height: i32,
+    pub logical_width: f64,
+    pub logical_height: f64,
+}
+
+impl MouseMovePayload {
+    pub fn new(point: OverlayPoint, screen_width: i32, screen_height: i32) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            point,
+            screen_width,
+            screen_height,
+            logical_width: screen_width as f64 / point.scale_factor,
+            logical_height: screen_height as f64 / point.scale_factor,
+        }
+    }
+}
+
+/// Payload of `global-mouse-down` and `global-mouse-up`.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MouseButtonPayload {
+    pub version: u32,
+    #[serde(flatten)]
+    pub point: OverlayPoint,
+    /// DOM `MouseEvent.button` numbering
+    pub button: u8,
+    pub modifiers: Modifiers,
+}
+
+impl MouseButtonPayload {
+    pub fn new(point: OverlayPoint, button: u8, modifiers: Modifiers) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            point,
+            button,
+            modifiers,
+        }
+    }
+}
+
+/// Payload of `global-scroll`.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ScrollPayload {
+    pub version: u32,
+    #[serde(flatten)]
+    pub point: OverlayPoint,
+    /// Wheel notches, positive to the right
+    pub delta_x: i32,
+    /// Wheel notches, positive downwards
+    pub delta_y: i32,
+    pub modifiers: Modifiers,
+}
+
+impl ScrollPayload {
+    pub fn new(point: OverlayPoint, delta_x: i32, delta_y: i32, modifiers: Modifiers) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            point,
+            delta_x,
+            delta_y,
+            modifiers,
+        }
+    }
+}
+
+/// Payload of `click-through-changed`.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ClickThroughPayload {
+    pub version: u32,
+    pub enabled: bool,
+}
+
+impl ClickThroughPayload {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            enabled,
+        }
+    }
+}
+
+/// Payload of `tracking-status-changed`.
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct TrackingStatusPayload {
+    pub version: u32,
+    #[serde(flatten)]
+    pub status: TrackingStatus,
+}
+
+impl TrackingStatusPayload {
+    pub fn new(status: TrackingStatus) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            status,
+        }
+    }
+}
+
+/// Payload of `monitors-changed`.
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct MonitorsChangedPayload {
+    pub version: u32,
+    pub monitors: Vec<Monitor>,
+}
+
+impl MonitorsChangedPayload {
+    pub fn new(monitors: Vec<Monitor>) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            monitors,
+        }
+    }
+}
+
+/// Payload of `config-changed`.
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConfigChangedPayload {
+    pub version: u32,
+    pub config: Config,
+}
+
+impl ConfigChangedPayload {
+    pub fn new(config: Config) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            config,
+        }
+    }
+}
+
+/// Payload of `config-warning`.
+#[derive(Clone, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct ConfigWarningPayload {
+    pub version: u32,
+    /// Why the config file on disk was not applied
+    pub message: String,
+}
+
+impl ConfigWarningPayload {
+    pub fn new(message: String) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            message,
+        }
+    }
+}
+
+/// Payload of `intensity-changed`.
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct IntensityPayload {
+    pub version: u32,
+    /// Factor for the splat force and brightness of the active profile,
+    /// 0 to 1, lowered by per-application rules
+    pub intensity: f64,
+}
+
+impl IntensityPayload {
+    pub fn new(intensity: f64) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            intensity,
+        }
+    }
+}
+
+/// Payload of the tray requests that carry no data (`open-settings`,
+/// `open-welcome`, ...).
+#[derive(Clone, Copy, Debug, Serialize, TS)]
+#[ts(export)]
+pub struct SignalPayload {
+    pub version: u32,
+}
+
+impl Default for SignalPayload {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+        }
+    }
+}
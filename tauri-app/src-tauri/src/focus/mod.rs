@@ -0,0 +1,156 @@
+// The window that has keyboard focus, whichever application owns it
+//
+// Sources implement `FocusSource`, so the rules and full-screen handling
+// can be driven by a scripted source instead of a display server.
+
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FocusedWindow {
+    /// Instance name from `WM_CLASS`, empty when unset
+    pub instance: String,
+    /// Class name from `WM_CLASS`, empty when unset
+    pub class: String,
+    pub title: String,
+    /// Process that owns the window, when it says (`_NET_WM_PID`)
+    pub pid: Option<u32>,
+    /// Name of that process, e.g. `code`
+    pub process: Option<String>,
+    /// Covers its whole monitor, like a game or a video player
+    pub fullscreen: bool,
+    /// Middle of the window in global coordinates, when known
+    pub center: Option<(i32, i32)>,
+}
+
+impl FocusedWindow {
+    /// True for the overlay's own windows.
+    pub fn is_ours(&self) -> bool {
+        self.pid == Some(std::process::id())
+    }
+}
+
+/// A platform backend able to tell which window has focus.
+pub trait FocusSource: Send {
+    /// The focused window, `None` when no window has focus.
+    fn focused(&mut self) -> Result<Option<FocusedWindow>, String>;
+
+    /// Blocks until focus moves or the focused window changes. An error
+    /// means the source is gone for good.
+    fn wait(&mut self) -> Result<(), String>;
+}
+
+/// Picks the focus source for this machine.
+pub fn default_source() -> Result<Box<dyn FocusSource>, String> {
+    #[cfg(target_os = "linux")]
+    {
+        let focus = x11::X11Focus::connect(None)?;
+        focus.select_changes()?;
+        Ok(Box::new(focus))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        Err("Following the focused window is not supported on this platform".to_string())
+    }
+}
+
+/// Blocks, calling `on_change` with the focused window of `source` first
+/// and then whenever it changes.
+///
+/// Returns only when the source fails, e.g. the display connection is lost.
+pub fn watch<F: FnMut(Option<FocusedWindow>)>(mut source: Box<dyn FocusSource>, mut on_change: F) -> Result<(), String> {
+    let mut current = source.focused()?;
+    on_change(current.clone());
+    loop {
+        source.wait()?;
+
+        let focused = source.focused()?;
+        if focused != current {
+            current = focused.clone();
+            on_change(focused);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Reports each scripted focus in turn, one per `wait`, then fails.
+    pub(crate) struct ScriptedFocus {
+        script: VecDeque<Option<FocusedWindow>>,
+    }
+
+    impl ScriptedFocus {
+        pub(crate) fn boxed(script: Vec<Option<FocusedWindow>>) -> Box<dyn FocusSource> {
+            Box::new(Self { script: script.into() })
+        }
+    }
+
+    impl FocusSource for ScriptedFocus {
+        fn focused(&mut self) -> Result<Option<FocusedWindow>, String> {
+            self.script.front().cloned().ok_or_else(|| "No window was scripted".to_string())
+        }
+
+        fn wait(&mut self) -> Result<(), String> {
+            self.script.pop_front();
+            if self.script.is_empty() {
+                return Err("Script ended".to_string());
+            }
+            Ok(())
+        }
+    }
+
+    /// A window of another application, with `class` as both `WM_CLASS` names.
+    pub(crate) fn window(class: &str, title: &str) -> Option<FocusedWindow> {
+        Some(FocusedWindow {
+            instance: class.to_lowercase(),
+            class: class.to_string(),
+            title: title.to_string(),
+            ..FocusedWindow::default()
+        })
+    }
+
+    #[test]
+    fn reports_the_first_focus_and_each_change_once() {
+        let source = ScriptedFocus::boxed(vec![
+            window("Firefox", "News"),
+            window("Firefox", "News"),
+            None,
+            window("Code", "main.rs"),
+        ]);
+        let mut seen = Vec::new();
+        let result = watch(source, |focused| seen.push(focused));
+
+        assert_eq!(result, Err("Script ended".to_string()));
+        assert_eq!(seen, vec![window("Firefox", "News"), None, window("Code", "main.rs")]);
+    }
+
+    #[test]
+    fn reports_a_title_change_in_the_same_window() {
+        let source = ScriptedFocus::boxed(vec![window("Code", "main.rs"), window("Code", "lib.rs")]);
+        let mut seen = Vec::new();
+        let _ = watch(source, |focused| seen.push(focused));
+
+        assert_eq!(seen, vec![window("Code", "main.rs"), window("Code", "lib.rs")]);
+    }
+
+    #[test]
+    fn stops_when_the_source_fails() {
+        let mut calls = 0;
+        let result = watch(ScriptedFocus::boxed(Vec::new()), |_| calls += 1);
+
+        assert!(result.is_err());
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn recognises_our_own_windows() {
+        let mut ours = window("magic-cursor", "Settings").unwrap();
+        assert!(!ours.is_ours());
+        ours.pid = Some(std::process::id());
+        assert!(ours.is_ours());
+    }
+}
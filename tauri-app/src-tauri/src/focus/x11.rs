@@ -0,0 +1,158 @@
+use std::fs;
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, EventMask, GetPropertyReply, Window,
+};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+use super::{FocusSource, FocusedWindow};
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_WM_STATE,
+        _NET_WM_STATE_FULLSCREEN,
+        _NET_WM_PID,
+        _NET_WM_NAME,
+    }
+}
+
+/// The window the window manager reports as active, through EWMH.
+pub struct X11Focus {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+    /// Active window whose property changes we listen to
+    watched: Option<Window>,
+}
+
+impl X11Focus {
+    /// Connects to `display`, or to `$DISPLAY` when `None`.
+    pub fn connect(display: Option<&str>) -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(display)
+            .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let atoms = Atoms::new(&conn)
+            .map_err(|e| format!("Failed to send InternAtom: {}", e))?
+            .reply()
+            .map_err(|e| format!("InternAtom failed: {}", e))?;
+
+        Ok(Self {
+            conn,
+            root,
+            atoms,
+            watched: None,
+        })
+    }
+
+    /// Subscribes to changes of the active window.
+    pub fn select_changes(&self) -> Result<(), String> {
+        self.select_properties(self.root)
+            .map_err(|e| format!("Failed to watch the active window: {}", e))
+    }
+
+    fn describe(&self, window: Window) -> Result<FocusedWindow, String> {
+        let state = self.property(window, self.atoms._NET_WM_STATE)?;
+        let class = self.property(window, AtomEnum::WM_CLASS.into())?.value;
+        let mut class = class.split(|&byte| byte == 0).map(|name| String::from_utf8_lossy(name).into_owned());
+
+        let title = self.property(window, self.atoms._NET_WM_NAME)?.value;
+        let title = if title.is_empty() {
+            self.property(window, AtomEnum::WM_NAME.into())?.value
+        } else {
+            title
+        };
+
+        let pid = self.property(window, self.atoms._NET_WM_PID)?
+            .value32()
+            .and_then(|mut values| values.next());
+        // The kernel's short name, as `ps` and `top` show it
+        let process = pid
+            .and_then(|pid| fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+            .map(|name| name.trim_end().to_string());
+
+        Ok(FocusedWindow {
+            instance: class.next().unwrap_or_default(),
+            class: class.next().unwrap_or_default(),
+            title: String::from_utf8_lossy(&title).into_owned(),
+            pid,
+            process,
+            fullscreen: state.value32().is_some_and(|mut atoms| atoms.any(|atom| atom == self.atoms._NET_WM_STATE_FULLSCREEN)),
+            center: self.center(window).ok(),
+        })
+    }
+
+    /// Middle of `window` in root window coordinates.
+    fn center(&self, window: Window) -> Result<(i32, i32), String> {
+        let geometry = self.conn.get_geometry(window)
+            .map_err(|e| format!("Failed to send GetGeometry: {}", e))?
+            .reply()
+            .map_err(|e| format!("GetGeometry failed: {}", e))?;
+        // Reparenting window managers put the window inside a frame
+        let origin = self.conn.translate_coordinates(window, self.root, 0, 0)
+            .map_err(|e| format!("Failed to send TranslateCoordinates: {}", e))?
+            .reply()
+            .map_err(|e| format!("TranslateCoordinates failed: {}", e))?;
+
+        Ok((
+            origin.dst_x as i32 + geometry.width as i32 / 2,
+            origin.dst_y as i32 + geometry.height as i32 / 2,
+        ))
+    }
+
+    fn select_properties(&self, window: Window) -> Result<(), String> {
+        let attributes = ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE);
+        self.conn.change_window_attributes(window, &attributes)
+            .map_err(|e| format!("Failed to send ChangeWindowAttributes: {}", e))?
+            .check()
+            .map_err(|e| format!("ChangeWindowAttributes failed: {}", e))
+    }
+
+    fn property(&self, window: Window, property: Atom) -> Result<GetPropertyReply, String> {
+        self.conn.get_property(false, window, property, AtomEnum::ANY, 0, u32::MAX)
+            .map_err(|e| format!("Failed to send GetProperty: {}", e))?
+            .reply()
+            .map_err(|e| format!("GetProperty failed: {}", e))
+    }
+}
+
+impl FocusSource for X11Focus {
+    /// Also follows the state of the window from now on, so `wait` sees it
+    /// going full-screen or changing its title.
+    fn focused(&mut self) -> Result<Option<FocusedWindow>, String> {
+        let active = self.property(self.root, self.atoms._NET_ACTIVE_WINDOW)?
+            .value32()
+            .and_then(|mut values| values.next())
+            .filter(|&window| window != 0);
+
+        if active != self.watched {
+            // The window may be gone already; its events stop either way
+            if let Some(window) = active {
+                let _ = self.select_properties(window);
+            }
+            self.watched = active;
+        }
+
+        // A window closing as we look is no longer focused
+        Ok(active.and_then(|window| self.describe(window).ok()))
+    }
+
+    /// Requires a prior call to `select_changes`.
+    fn wait(&mut self) -> Result<(), String> {
+        loop {
+            let event = self.conn.wait_for_event()
+                .map_err(|e| format!("Lost connection to X server: {}", e))?;
+
+            if let Event::PropertyNotify(notify) = event {
+                let active_changed = notify.window == self.root && notify.atom == self.atoms._NET_ACTIVE_WINDOW;
+                let window_changed = Some(notify.window) == self.watched
+                    && [self.atoms._NET_WM_STATE, self.atoms._NET_WM_NAME, AtomEnum::WM_NAME.into()].contains(&notify.atom);
+                if active_changed || window_changed {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+// Steps aside for full-screen applications
+//
+// While a full-screen window of another application has focus, the overlay
+// on its monitor is paused or hidden, as `fullscreen_app` says, and brought
+// back once focus leaves. Like the rule engine, the guard only decides; the
+// caller carries out the returned effects on the overlay windows.
+
+use crate::config::FullscreenAction;
+use crate::overlay::MAIN_LABEL;
+
+/// A change to the overlay window with the label `overlay`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    SetPaused { overlay: String, paused: bool },
+    SetVisible { overlay: String, visible: bool },
+}
+
+#[derive(Default)]
+pub struct FullscreenGuard {
+    /// Overlay under the focused full-screen window
+    covered: Option<String>,
+    /// What was done to it, to undo once focus leaves
+    suppressed: Option<FullscreenAction>,
+}
+
+impl FullscreenGuard {
+    /// Records that a full-screen window on the monitor of the overlay
+    /// `covered` has focus, or none does, and returns what to change:
+    /// undoing what was done to the previous overlay, then pausing or hiding
+    /// the new one as `action` says.
+    ///
+    /// `paused` and `visible` are the current state of tracking and of the
+    /// main overlay; what the user already paused or hid is left alone.
+    pub fn focus_changed(
+        &mut self,
+        covered: Option<&str>,
+        action: FullscreenAction,
+        paused: bool,
+        visible: bool,
+    ) -> Vec<Effect> {
+        if covered == self.covered.as_deref() {
+            return Vec::new();
+        }
+
+        let mut effects = Vec::new();
+        let mut visible = visible;
+        if let (Some(overlay), Some(suppressed)) = (self.covered.take(), self.suppressed.take()) {
+            match suppressed {
+                FullscreenAction::Pause => effects.push(Effect::SetPaused { overlay, paused: false }),
+                FullscreenAction::Hide => {
+                    // Hidden by us, so shown as far as the user is concerned
+                    visible |= overlay == MAIN_LABEL;
+                    effects.push(Effect::SetVisible { overlay, visible: true });
+                }
+                FullscreenAction::Ignore => {}
+            }
+        }
+
+        if let Some(overlay) = covered {
+            self.suppressed = match action {
+                FullscreenAction::Pause if !paused => {
+                    effects.push(Effect::SetPaused { overlay: overlay.to_string(), paused: true });
+                    Some(action)
+                }
+                FullscreenAction::Hide if visible => {
+                    effects.push(Effect::SetVisible { overlay: overlay.to_string(), visible: false });
+                    Some(action)
+                }
+                _ => None,
+            };
+            self.covered = Some(overlay.to_string());
+        }
+        effects
+    }
+
+    /// The user paused or resumed tracking (`Pause`), or showed or hid the
+    /// overlays (`Hide`), by hand. That wins over the guard, which stops
+    /// holding back the covered overlay until focus moves on.
+    pub fn overridden(&mut self, action: FullscreenAction) -> Vec<Effect> {
+        if self.suppressed != Some(action) {
+            return Vec::new();
+        }
+        self.suppressed = None;
+
+        // Showing and hiding by hand already applies to every overlay
+        match (action, &self.covered) {
+            (FullscreenAction::Pause, Some(overlay)) => vec![Effect::SetPaused { overlay: overlay.clone(), paused: false }],
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AppRule, RuleAction};
+    use crate::focus::{tests::window, FocusedWindow};
+    use crate::rules::{self, RuleEngine};
+    use std::collections::BTreeSet;
+
+    const SIDE: &str = "overlay-DP-2";
+
+    /// Carries out effects the way the app does, on a main overlay and one
+    /// to its side.
+    struct App {
+        guard: FullscreenGuard,
+        action: FullscreenAction,
+        engine: RuleEngine,
+        rules: Vec<AppRule>,
+        paused: bool,
+        paused_overlays: BTreeSet<String>,
+        hidden_overlays: BTreeSet<String>,
+    }
+
+    impl App {
+        fn new(action: FullscreenAction) -> Self {
+            Self {
+                guard: FullscreenGuard::default(),
+                action,
+                engine: RuleEngine::default(),
+                rules: Vec::new(),
+                paused: false,
+                paused_overlays: BTreeSet::new(),
+                hidden_overlays: BTreeSet::new(),
+            }
+        }
+
+        fn focus(&mut self, covered: Option<&str>) -> Vec<Effect> {
+            let visible = !self.hidden_overlays.contains(MAIN_LABEL);
+            let effects = self.guard.focus_changed(covered, self.action, self.paused, visible);
+            self.apply(&effects);
+            effects
+        }
+
+        /// Runs the rules, then the guard, as `on_focus_changed` does.
+        fn focus_window(&mut self, focused: Option<FocusedWindow>, covered: Option<&str>) {
+            for effect in self.engine.focus_changed(focused, &self.rules, "Default", self.paused) {
+                if let rules::Effect::SetPaused(paused) = effect {
+                    self.paused = paused;
+                }
+            }
+            self.focus(covered);
+        }
+
+        fn apply(&mut self, effects: &[Effect]) {
+            for effect in effects {
+                match effect {
+                    Effect::SetPaused { overlay, paused: true } => self.paused_overlays.insert(overlay.clone()),
+                    Effect::SetPaused { overlay, paused: false } => self.paused_overlays.remove(overlay),
+                    Effect::SetVisible { overlay, visible: false } => self.hidden_overlays.insert(overlay.clone()),
+                    Effect::SetVisible { overlay, visible: true } => self.hidden_overlays.remove(overlay),
+                };
+            }
+        }
+
+        fn set_visible(&mut self, visible: bool) {
+            let effects = self.guard.overridden(FullscreenAction::Hide);
+            self.apply(&effects);
+            self.hidden_overlays = if visible {
+                BTreeSet::new()
+            } else {
+                [MAIN_LABEL, SIDE].map(str::to_string).into()
+            };
+        }
+
+        fn set_paused(&mut self, paused: bool) {
+            let effects = self.guard.overridden(FullscreenAction::Pause);
+            self.apply(&effects);
+            self.paused = paused;
+        }
+    }
+
+    fn labels(set: &BTreeSet<String>) -> Vec<&str> {
+        set.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn pauses_only_the_covered_overlay_until_focus_leaves() {
+        let mut app = App::new(FullscreenAction::Pause);
+        assert_eq!(app.focus(Some(SIDE)), vec![Effect::SetPaused { overlay: SIDE.to_string(), paused: true }]);
+        assert_eq!(labels(&app.paused_overlays), [SIDE]);
+        assert!(!app.paused);
+
+        assert_eq!(app.focus(Some(SIDE)), Vec::new());
+        assert_eq!(app.focus(None), vec![Effect::SetPaused { overlay: SIDE.to_string(), paused: false }]);
+        assert!(app.paused_overlays.is_empty());
+    }
+
+    #[test]
+    fn hides_only_the_covered_overlay_until_focus_leaves() {
+        let mut app = App::new(FullscreenAction::Hide);
+        app.focus(Some(MAIN_LABEL));
+        assert_eq!(labels(&app.hidden_overlays), [MAIN_LABEL]);
+
+        app.focus(None);
+        assert!(app.hidden_overlays.is_empty());
+    }
+
+    #[test]
+    fn follows_focus_to_another_monitor() {
+        let mut app = App::new(FullscreenAction::Hide);
+        app.focus(Some(MAIN_LABEL));
+        let effects = app.focus(Some(SIDE));
+
+        assert_eq!(effects, vec![
+            Effect::SetVisible { overlay: MAIN_LABEL.to_string(), visible: true },
+            Effect::SetVisible { overlay: SIDE.to_string(), visible: false },
+        ]);
+        assert_eq!(labels(&app.hidden_overlays), [SIDE]);
+    }
+
+    #[test]
+    fn leaves_alone_what_the_user_already_paused_or_hid() {
+        let mut app = App::new(FullscreenAction::Pause);
+        app.set_paused(true);
+        assert_eq!(app.focus(Some(MAIN_LABEL)), Vec::new());
+        assert_eq!(app.focus(None), Vec::new());
+        assert!(app.paused);
+
+        let mut app = App::new(FullscreenAction::Hide);
+        app.set_visible(false);
+        assert_eq!(app.focus(Some(SIDE)), Vec::new());
+        assert_eq!(app.focus(None), Vec::new());
+        assert_eq!(app.hidden_overlays.len(), 2);
+    }
+
+    #[test]
+    fn hands_the_overlay_back_when_the_user_steps_in() {
+        let mut app = App::new(FullscreenAction::Pause);
+        app.focus(Some(SIDE));
+        app.set_paused(false);
+        assert!(app.paused_overlays.is_empty());
+        assert_eq!(app.focus(None), Vec::new());
+
+        let mut app = App::new(FullscreenAction::Hide);
+        app.focus(Some(SIDE));
+        app.set_visible(false);
+        assert_eq!(app.focus(None), Vec::new());
+        assert_eq!(app.hidden_overlays.len(), 2);
+    }
+
+    #[test]
+    fn ignore_changes_nothing() {
+        let mut app = App::new(FullscreenAction::Ignore);
+        assert_eq!(app.focus(Some(MAIN_LABEL)), Vec::new());
+        assert_eq!(app.focus(Some(SIDE)), Vec::new());
+        assert_eq!(app.focus(None), Vec::new());
+    }
+
+    #[test]
+    fn rule_pauses_survive_the_full_screen_pause() {
+        let mut app = App::new(FullscreenAction::Pause);
+        app.rules.push(AppRule {
+            wm_class: Some("mpv".to_string()),
+            action: RuleAction::Pause,
+            ..AppRule::default()
+        });
+        let mut game = window("Game", "Game").unwrap();
+        game.fullscreen = true;
+
+        // From a full-screen game to a windowed video, and on
+        app.focus_window(Some(game.clone()), Some(MAIN_LABEL));
+        app.focus_window(window("mpv", "video.mkv"), None);
+        assert!(app.paused);
+        assert!(app.paused_overlays.is_empty());
+        app.focus_window(window("Firefox", "News"), None);
+        assert!(!app.paused);
+
+        // From a full-screen video to the game, and on
+        let mut video = window("mpv", "video.mkv").unwrap();
+        video.fullscreen = true;
+        app.focus_window(Some(video), Some(MAIN_LABEL));
+        assert!(app.paused);
+        assert!(app.paused_overlays.is_empty());
+        app.focus_window(Some(game), Some(SIDE));
+        assert!(!app.paused);
+        assert_eq!(labels(&app.paused_overlays), [SIDE]);
+        app.focus_window(None, None);
+        assert!(!app.paused);
+        assert!(app.paused_overlays.is_empty());
+    }
+}
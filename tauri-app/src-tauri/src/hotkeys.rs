@@ -0,0 +1,223 @@
+// Global keyboard shortcuts grabbed on the X server
+//
+// Passive key grabs on the root window send the shortcut to us whichever
+// window has focus, so they work while the overlay is click-through and
+// never gets keyboard focus. A combination another application has grabbed
+// first can't be taken; `bind` reports those as conflicts. Under Wayland the
+// grabs only see keys pressed while an XWayland window has focus.
+
+use std::sync::Mutex;
+use x11rb::connection::Connection;
+use x11rb::errors::ReplyError;
+use x11rb::protocol::xproto::{
+    ConnectionExt as _, GrabMode, KeyButMask, Keycode, Mapping, ModMask, Window,
+};
+use x11rb::protocol::{ErrorKind, Event};
+use x11rb::rust_connection::RustConnection;
+
+use crate::config::{Hotkey, HotkeyAction};
+use crate::cursor::Modifiers;
+
+/// Lock modifiers a shortcut must work with: none, Caps Lock, Num Lock
+/// (usually Mod2) and both.
+fn lock_masks() -> [ModMask; 4] {
+    [ModMask::from(0u16), ModMask::LOCK, ModMask::M2, ModMask::LOCK | ModMask::M2]
+}
+
+struct Grab {
+    action: HotkeyAction,
+    keycode: Keycode,
+    modifiers: ModMask,
+}
+
+#[derive(Default)]
+struct Bound {
+    bindings: Vec<(HotkeyAction, Hotkey)>,
+    grabs: Vec<Grab>,
+}
+
+pub struct GlobalHotkeys {
+    conn: RustConnection,
+    root: Window,
+    bound: Mutex<Bound>,
+}
+
+impl GlobalHotkeys {
+    /// Connects to `display`, or to `$DISPLAY` when `None`.
+    pub fn connect(display: Option<&str>) -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(display)
+            .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+
+        Ok(Self {
+            conn,
+            root,
+            bound: Mutex::new(Bound::default()),
+        })
+    }
+
+    /// Grabs `bindings` in place of the previous shortcuts.
+    ///
+    /// Returns a message for each shortcut that couldn't be grabbed, most
+    /// often because another application already uses it.
+    pub fn bind(&self, bindings: &[(HotkeyAction, Hotkey)]) -> Vec<String> {
+        let mut bound = match self.bound.lock() {
+            Ok(guard) => guard,
+            Err(e) => return vec![format!("Failed to lock shortcuts: {}", e)],
+        };
+        for grab in bound.grabs.drain(..) {
+            for lock in lock_masks() {
+                let _ = self.conn.ungrab_key(grab.keycode, self.root, grab.modifiers | lock);
+            }
+        }
+
+        let mapping = match self.keyboard_mapping() {
+            Ok(mapping) => mapping,
+            Err(e) => return vec![e],
+        };
+
+        let mut problems = Vec::new();
+        for &(action, hotkey) in bindings {
+            let Some(keycode) = mapping.keycode(hotkey.keysym) else {
+                problems.push(format!("{} ({}): no key on this keyboard types it", hotkey, action.description()));
+                continue;
+            };
+
+            let grab = Grab {
+                action,
+                keycode,
+                modifiers: mod_mask(hotkey.modifiers),
+            };
+            match self.grab(&grab) {
+                Ok(()) => bound.grabs.push(grab),
+                Err(e) => problems.push(format!("{} ({}): {}", hotkey, action.description(), e)),
+            }
+        }
+
+        bound.bindings = bindings.to_vec();
+        let _ = self.conn.flush();
+        problems
+    }
+
+    /// Blocks, calling `on_action` each time a bound shortcut is pressed.
+    ///
+    /// Returns only when the connection to the X server is lost.
+    pub fn run<F: FnMut(HotkeyAction)>(&self, mut on_action: F) -> Result<(), String> {
+        let mut pending = None;
+        loop {
+            let event = match pending.take() {
+                Some(event) => event,
+                None => self.conn.wait_for_event()
+                    .map_err(|e| format!("Lost connection to X server: {}", e))?,
+            };
+
+            match event {
+                Event::KeyPress(press) => {
+                    if let Some(action) = self.action(press.detail, press.state) {
+                        on_action(action);
+                    }
+                }
+                // Auto-repeat sends a release immediately followed by a press
+                // with the same timestamp; drop both so holding the keys
+                // doesn't toggle back and forth
+                Event::KeyRelease(release) => {
+                    pending = self.conn.poll_for_event()
+                        .map_err(|e| format!("Lost connection to X server: {}", e))?;
+                    if let Some(Event::KeyPress(press)) = &pending {
+                        if press.detail == release.detail && press.time == release.time {
+                            pending = None;
+                        }
+                    }
+                }
+                Event::MappingNotify(notify) if notify.request == Mapping::KEYBOARD => {
+                    // The keyboard layout changed, so keycodes may have moved
+                    let bindings = self.bound.lock()
+                        .map(|bound| bound.bindings.clone())
+                        .unwrap_or_default();
+                    for problem in self.bind(&bindings) {
+                        eprintln!("Shortcut unavailable after keyboard change: {}", problem);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn grab(&self, grab: &Grab) -> Result<(), String> {
+        let locks = lock_masks();
+        for (index, &lock) in locks.iter().enumerate() {
+            let result = self.conn
+                .grab_key(false, self.root, grab.modifiers | lock, grab.keycode, GrabMode::ASYNC, GrabMode::ASYNC)
+                .map_err(|e| format!("Failed to send GrabKey: {}", e))?
+                .check();
+
+            if let Err(e) = result {
+                for lock in &locks[..index] {
+                    let _ = self.conn.ungrab_key(grab.keycode, self.root, grab.modifiers | *lock);
+                }
+                return Err(match e {
+                    ReplyError::X11Error(error) if error.error_kind == ErrorKind::Access => {
+                        "already used by another application".to_string()
+                    }
+                    e => format!("GrabKey failed: {}", e),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn action(&self, keycode: Keycode, state: KeyButMask) -> Option<HotkeyAction> {
+        // Ignore the lock modifiers, like the grabs do
+        let held = ModMask::from(u16::from(state))
+            & (ModMask::SHIFT | ModMask::CONTROL | ModMask::M1 | ModMask::M4);
+        let bound = self.bound.lock().ok()?;
+        bound.grabs.iter()
+            .find(|grab| grab.keycode == keycode && grab.modifiers == held)
+            .map(|grab| grab.action)
+    }
+
+    fn keyboard_mapping(&self) -> Result<KeyboardMapping, String> {
+        let setup = self.conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let reply = self.conn.get_keyboard_mapping(min, max - min + 1)
+            .map_err(|e| format!("Failed to send GetKeyboardMapping: {}", e))?
+            .reply()
+            .map_err(|e| format!("GetKeyboardMapping failed: {}", e))?;
+
+        Ok(KeyboardMapping {
+            min_keycode: min,
+            keysyms_per_keycode: reply.keysyms_per_keycode.max(1) as usize,
+            keysyms: reply.keysyms,
+        })
+    }
+}
+
+struct KeyboardMapping {
+    min_keycode: Keycode,
+    keysyms_per_keycode: usize,
+    keysyms: Vec<u32>,
+}
+
+impl KeyboardMapping {
+    /// The first key that types `keysym` without modifiers, or with Shift.
+    fn keycode(&self, keysym: u32) -> Option<Keycode> {
+        self.keysyms.chunks(self.keysyms_per_keycode)
+            .position(|keysyms| keysyms.iter().take(2).any(|&other| other == keysym))
+            .and_then(|index| Keycode::try_from(index + self.min_keycode as usize).ok())
+    }
+}
+
+fn mod_mask(modifiers: Modifiers) -> ModMask {
+    let mut mask = ModMask::from(0u16);
+    for (held, modifier) in [
+        (modifiers.shift, ModMask::SHIFT),
+        (modifiers.control, ModMask::CONTROL),
+        (modifiers.alt, ModMask::M1),
+        (modifiers.meta, ModMask::M4),
+    ] {
+        if held {
+            mask |= modifier;
+        }
+    }
+    mask
+}
@@ -0,0 +1,171 @@
+// Interactive regions of the main overlay
+//
+// Click-through normally lets every click pass the full-screen overlay. The
+// frontend can name rectangles, such as a floating control or an open panel,
+// that keep taking input while click-through is on; the rest of the screen
+// stays pass-through. On Linux this is the window's input shape, which GDK
+// sets as an X11 Shape extension input region. Other platforms keep
+// all-or-nothing click-through and ignore the regions.
+
+use serde::{Deserialize, Serialize};
+use ts_rs::TS;
+
+/// Most regions accepted at once; a handful of panels need far fewer.
+pub const MAX_REGIONS: usize = 64;
+/// X11 shapes use 16-bit coordinates.
+const MAX_COORDINATE: f64 = i16::MAX as f64;
+
+/// A rectangle in CSS pixels, relative to the top-left corner of the window
+/// (as returned by `getBoundingClientRect`).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, TS)]
+#[ts(export)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    fn check(&self) -> Result<(), String> {
+        if ![self.x, self.y, self.width, self.height].iter().all(|value| value.is_finite()) {
+            return Err(format!("{:?} is not finite", self));
+        }
+        if self.width < 0.0 || self.height < 0.0 {
+            return Err(format!("{:?} has a negative size", self));
+        }
+        let corners = [self.x, self.y, self.x + self.width, self.y + self.height];
+        if corners.iter().any(|value| value.abs() > MAX_COORDINATE) {
+            return Err(format!("{:?} reaches past {} pixels", self, MAX_COORDINATE));
+        }
+        Ok(())
+    }
+
+    /// The whole pixels it touches, as `(x, y, width, height)`; `None`
+    /// when it covers no pixel.
+    pub fn to_pixels(&self) -> Option<(i32, i32, i32, i32)> {
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return None;
+        }
+        let left = self.x.floor();
+        let top = self.y.floor();
+        let right = (self.x + self.width).ceil();
+        let bottom = (self.y + self.height).ceil();
+
+        Some((left as i32, top as i32, (right - left) as i32, (bottom - top) as i32))
+    }
+}
+
+/// Checks regions sent by the frontend.
+pub fn check(regions: &[Rect]) -> Result<(), String> {
+    if regions.len() > MAX_REGIONS {
+        return Err(format!("At most {} interactive regions are supported, got {}", MAX_REGIONS, regions.len()));
+    }
+    for (index, region) in regions.iter().enumerate() {
+        region.check().map_err(|e| format!("Invalid interactive region {}: {}", index, e))?;
+    }
+    Ok(())
+}
+
+/// Sets which parts of `window` take pointer input: all of it with
+/// click-through off, otherwise only `regions`.
+///
+/// Replaces `set_ignore_cursor_events`, which would reset the shape from
+/// the event loop at an unpredictable later point.
+#[cfg(target_os = "linux")]
+pub fn apply(window: &tauri::Window, click_through: bool, regions: &[Rect]) -> Result<(), String> {
+    use gtk::cairo::{RectangleInt, Region};
+    use gtk::prelude::*;
+
+    // GDK takes shapes in logical pixels, which CSS pixels are, and scales
+    // them by the window scale itself
+    let rectangles: Vec<RectangleInt> = regions.iter()
+        .filter_map(Rect::to_pixels)
+        .map(|(x, y, width, height)| RectangleInt { x, y, width, height })
+        .collect();
+
+    // GTK objects may only be touched on the main thread
+    let target = window.clone();
+    window.run_on_main_thread(move || {
+        let gtk_window = match target.gtk_window() {
+            Ok(gtk_window) => gtk_window,
+            Err(e) => {
+                eprintln!("Failed to get GTK window: {}", e);
+                return;
+            }
+        };
+        if !click_through {
+            gtk_window.input_shape_combine_region(None);
+            return;
+        }
+        let Some(gdk_window) = gtk_window.window() else {
+            eprintln!("Overlay window is not realized, input region not set");
+            return;
+        };
+
+        // Like tao, keep one pixel: an empty shape can mean "unshaped"
+        let region = if rectangles.is_empty() {
+            Region::create_rectangle(&RectangleInt { x: 0, y: 0, width: 1, height: 1 })
+        } else {
+            Region::create_rectangles(&rectangles)
+        };
+        gdk_window.input_shape_combine_region(&region, 0, 0);
+    })
+    .map_err(|e| format!("Failed to update input region: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    #[test]
+    fn to_pixels_covers_every_pixel_touched() {
+        assert_eq!(rect(10.0, 20.0, 30.0, 40.0).to_pixels(), Some((10, 20, 30, 40)));
+        assert_eq!(rect(10.5, 20.25, 30.0, 0.5).to_pixels(), Some((10, 20, 31, 1)));
+        assert_eq!(rect(-2.5, -0.5, 1.0, 1.0).to_pixels(), Some((-3, -1, 2, 2)));
+    }
+
+    #[test]
+    fn to_pixels_skips_empty_rects() {
+        assert_eq!(rect(10.0, 20.0, 0.0, 40.0).to_pixels(), None);
+        assert_eq!(rect(10.0, 20.0, 30.0, 0.0).to_pixels(), None);
+    }
+
+    #[test]
+    fn check_accepts_rects_on_and_off_the_window() {
+        let regions = [rect(0.0, 0.0, 0.0, 0.0), rect(-100.0, 50.5, 200.0, 30.0), rect(0.0, 0.0, 32767.0, 32767.0)];
+        assert_eq!(check(&regions), Ok(()));
+        assert_eq!(check(&[]), Ok(()));
+    }
+
+    #[test]
+    fn check_rejects_unusable_rects() {
+        for (region, problem) in [
+            (rect(f64::NAN, 0.0, 10.0, 10.0), "is not finite"),
+            (rect(0.0, 0.0, f64::INFINITY, 10.0), "is not finite"),
+            (rect(0.0, 0.0, -1.0, 10.0), "has a negative size"),
+            (rect(0.0, 0.0, 10.0, -0.5), "has a negative size"),
+            (rect(32000.0, 0.0, 1000.0, 10.0), "reaches past 32767 pixels"),
+            (rect(0.0, -40000.0, 10.0, 10.0), "reaches past 32767 pixels"),
+            (rect(0.0, 0.0, 1e300, 10.0), "reaches past 32767 pixels"),
+        ] {
+            let error = check(&[rect(0.0, 0.0, 1.0, 1.0), region]).unwrap_err();
+            assert!(error.starts_with("Invalid interactive region 1: "), "{}", error);
+            assert!(error.ends_with(problem), "{}", error);
+        }
+    }
+
+    #[test]
+    fn check_limits_the_number_of_regions() {
+        let regions = vec![rect(0.0, 0.0, 1.0, 1.0); MAX_REGIONS + 1];
+        assert!(check(&regions[..MAX_REGIONS]).is_ok());
+        assert_eq!(
+            check(&regions),
+            Err(format!("At most {} interactive regions are supported, got {}", MAX_REGIONS, MAX_REGIONS + 1))
+        );
+    }
+}
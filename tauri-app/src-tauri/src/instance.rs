@@ -0,0 +1,187 @@
+// Single-instance lock
+//
+// The first instance listens on a Unix socket in `$XDG_RUNTIME_DIR`. A later
+// launch finds the socket, sends its command-line arguments to the running
+// instance and exits, so there is never more than one set of overlays.
+
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::socket;
+
+const SOCKET_NAME: &str = "magic-cursor.sock";
+
+/// How long a later launch waits for the running instance to answer.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// One later launch, as sent over the socket.
+///
+/// Paths and arguments go as raw bytes, since they need not be UTF-8.
+#[derive(Serialize, Deserialize)]
+struct Launch {
+    /// Working directory, for resolving relative paths in `args`
+    cwd: Vec<u8>,
+    args: Vec<Vec<u8>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Reply {
+    error: Option<String>,
+}
+
+pub enum Claim {
+    /// No other instance is running; this one now owns the socket.
+    Primary(InstanceServer),
+    /// The arguments went to the running instance, with its answer.
+    Forwarded(Result<(), String>),
+}
+
+pub struct InstanceServer {
+    listener: UnixListener,
+}
+
+/// Becomes the running instance, or hands `args` to the one already running.
+pub fn claim(args: &[OsString]) -> Result<Claim, String> {
+    let path = socket::runtime_path(SOCKET_NAME)?;
+
+    for _ in 0..2 {
+        match UnixListener::bind(&path) {
+            Ok(listener) => {
+                socket::restrict(&path)?;
+                return Ok(Claim::Primary(InstanceServer { listener }));
+            }
+            Err(e) if e.kind() == ErrorKind::AddrInUse => {}
+            Err(e) => return Err(format!("Failed to bind {}: {}", path.display(), e)),
+        }
+
+        match UnixStream::connect(&path) {
+            Ok(stream) => return Ok(Claim::Forwarded(forward(stream, args))),
+            // Left behind by an instance that didn't exit cleanly
+            Err(e) if matches!(e.kind(), ErrorKind::ConnectionRefused | ErrorKind::NotFound) => {
+                let _ = fs::remove_file(&path);
+            }
+            Err(e) => return Err(format!("Failed to connect to {}: {}", path.display(), e)),
+        }
+    }
+
+    Err(format!("Another instance keeps replacing {}", path.display()))
+}
+
+impl InstanceServer {
+    /// Blocks, calling `on_launch` with the arguments and working directory
+    /// of every later launch. Its result is reported back to that launch.
+    pub fn serve<F>(self, mut on_launch: F)
+    where
+        F: FnMut(Vec<OsString>, PathBuf) -> Result<(), String>,
+    {
+        for stream in self.listener.incoming() {
+            let result = stream
+                .map_err(|e| format!("Failed to accept connection: {}", e))
+                .and_then(|stream| handle(stream, &mut on_launch));
+            if let Err(e) = result {
+                eprintln!("Ignored a second launch: {}", e);
+            }
+        }
+    }
+}
+
+fn handle<F>(stream: UnixStream, on_launch: &mut F) -> Result<(), String>
+where
+    F: FnMut(Vec<OsString>, PathBuf) -> Result<(), String>,
+{
+    socket::check_peer(&stream)?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(|e| format!("Failed to set socket timeout: {}", e))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)
+        .map_err(|e| format!("Failed to read arguments: {}", e))?;
+    let launch: Launch = serde_json::from_str(&line)
+        .map_err(|e| format!("Invalid launch message: {}", e))?;
+
+    let args: Vec<OsString> = launch.args.into_iter().map(OsString::from_vec).collect();
+    println!("Second launch with arguments {:?}", args);
+    let result = on_launch(args, OsString::from_vec(launch.cwd).into());
+
+    let reply = Reply { error: result.err() };
+    write_line(&stream, &reply)
+}
+
+fn forward(stream: UnixStream, args: &[OsString]) -> Result<(), String> {
+    stream.set_read_timeout(Some(REPLY_TIMEOUT))
+        .map_err(|e| format!("Failed to set socket timeout: {}", e))?;
+
+    let launch = Launch {
+        cwd: env::current_dir().unwrap_or_default().into_os_string().into_vec(),
+        args: args.iter().map(|arg| arg.as_bytes().to_vec()).collect(),
+    };
+    write_line(&stream, &launch)?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)
+        .map_err(|e| format!("The running instance did not answer: {}", e))?;
+    let reply: Reply = serde_json::from_str(&line)
+        .map_err(|e| format!("Invalid answer from the running instance: {}", e))?;
+    reply.error.map_or(Ok(()), Err)
+}
+
+fn write_line<T: Serialize>(mut stream: &UnixStream, message: &T) -> Result<(), String> {
+    let mut line = serde_json::to_string(message)
+        .map_err(|e| format!("Failed to serialize message: {}", e))?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+        .map_err(|e| format!("Failed to send message: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Forwards `args` to a `handle` answering with `result`, returning
+    /// what `handle` received and what `forward` returned.
+    fn round_trip(args: &[OsString], result: Result<(), String>) -> (Vec<OsString>, PathBuf, Result<(), String>) {
+        let (client, server) = UnixStream::pair().unwrap();
+        let running = thread::spawn(move || {
+            let mut received = None;
+            handle(server, &mut |args, cwd| {
+                received = Some((args, cwd));
+                result.clone()
+            }).unwrap();
+            received.unwrap()
+        });
+
+        let forwarded = forward(client, args);
+        let (received, cwd) = running.join().unwrap();
+        (received, cwd, forwarded)
+    }
+
+    #[test]
+    fn forwards_arguments_byte_for_byte() {
+        let args = vec![
+            OsString::from("--profile"),
+            OsString::from("Übersicht"),
+            OsString::from_vec(b"caf\xe9.magiccursor".to_vec()),
+        ];
+        let (received, cwd, result) = round_trip(&args, Ok(()));
+
+        assert_eq!(received, args);
+        assert_eq!(cwd, env::current_dir().unwrap());
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn reports_the_running_instance_error() {
+        let args = vec![OsString::from("--profile"), OsString::from("Nope")];
+        let (_, _, result) = round_trip(&args, Err("Profile 'Nope' does not exist".to_string()));
+
+        assert_eq!(result, Err("Profile 'Nope' does not exist".to_string()));
+    }
+}
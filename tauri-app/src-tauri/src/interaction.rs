@@ -0,0 +1,129 @@
+// Interaction leases
+//
+// A panel that needs the mouse holds a lease while it is open. The first
+// lease turns click-through off and releasing the last one restores the
+// user's choice, so overlapping panels can't leave it in the wrong state.
+// Leases belong to the window that took them and end with its page, which
+// covers reloads and crashed frontends.
+
+use std::collections::HashMap;
+
+pub type LeaseId = u64;
+
+#[derive(Default)]
+pub struct Leases {
+    next_id: LeaseId,
+    /// Label of the window holding each lease
+    held: HashMap<LeaseId, String>,
+    /// Click-through to restore, while any lease is held
+    preference: Option<bool>,
+}
+
+impl Leases {
+    /// Takes a lease for `window`; `click_through` is the current state,
+    /// restored once every lease is released.
+    pub fn acquire(&mut self, window: &str, click_through: bool) -> LeaseId {
+        if self.held.is_empty() {
+            self.preference = Some(click_through);
+        }
+        self.next_id += 1;
+        self.held.insert(self.next_id, window.to_string());
+        self.next_id
+    }
+
+    /// Ends a lease. Returns the click-through to restore when it was the
+    /// last one.
+    pub fn release(&mut self, id: LeaseId) -> Result<Option<bool>, String> {
+        self.held.remove(&id)
+            .ok_or_else(|| format!("Interaction lease {} is not held", id))?;
+        Ok(self.restore())
+    }
+
+    /// Ends every lease of `window`, like `release`.
+    pub fn release_window(&mut self, window: &str) -> Option<bool> {
+        let count = self.held.len();
+        self.held.retain(|_, owner| owner != window);
+        if self.held.len() == count {
+            return None;
+        }
+        self.restore()
+    }
+
+    /// The click-through to restore, while any lease is held.
+    pub fn deferred(&self) -> Option<bool> {
+        self.preference
+    }
+
+    /// While leases are held, remembers `click_through` for when the last
+    /// one is released instead of applying it now. Returns whether it did.
+    pub fn defer(&mut self, click_through: bool) -> bool {
+        match &mut self.preference {
+            Some(preference) => {
+                *preference = click_through;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn restore(&mut self) -> Option<bool> {
+        if self.held.is_empty() {
+            self.preference.take()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restores_the_choice_when_the_last_lease_ends() {
+        let mut leases = Leases::default();
+        let settings = leases.acquire("main", true);
+        let welcome = leases.acquire("main", false);
+        assert_ne!(settings, welcome);
+        assert_eq!(leases.deferred(), Some(true));
+
+        assert_eq!(leases.release(settings), Ok(None));
+        assert_eq!(leases.release(welcome), Ok(Some(true)));
+        assert_eq!(leases.deferred(), None);
+    }
+
+    #[test]
+    fn refuses_to_release_a_lease_twice() {
+        let mut leases = Leases::default();
+        let id = leases.acquire("main", false);
+        assert_eq!(leases.release(id), Ok(Some(false)));
+        assert!(leases.release(id).is_err());
+        assert!(leases.release(42).is_err());
+    }
+
+    #[test]
+    fn releases_every_lease_of_a_closed_window() {
+        let mut leases = Leases::default();
+        leases.acquire("main", true);
+        leases.acquire("main", true);
+        let overlay = leases.acquire("overlay-1", true);
+
+        assert_eq!(leases.release_window("overlay-2"), None);
+        assert_eq!(leases.release_window("main"), None);
+        assert_eq!(leases.release(overlay), Ok(Some(true)));
+        assert_eq!(leases.release_window("main"), None);
+    }
+
+    #[test]
+    fn defers_changes_only_while_leases_are_held() {
+        let mut leases = Leases::default();
+        assert!(!leases.defer(true));
+        assert_eq!(leases.deferred(), None);
+
+        let id = leases.acquire("main", false);
+        assert!(leases.defer(true));
+        assert_eq!(leases.deferred(), Some(true));
+        assert_eq!(leases.release(id), Ok(Some(true)));
+        assert!(!leases.defer(false));
+    }
+}
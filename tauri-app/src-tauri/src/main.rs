@@ -1,16 +1,72 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::{
-    CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem,
-    Window, State, AppHandle,
-};
+use tauri::{Manager, SystemTray, SystemTrayEvent, Window, State, AppHandle};
+use std::cell::Cell;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
+mod cli;
+mod config;
+#[cfg(target_os = "linux")]
+mod control;
+mod cursor;
+#[cfg(target_os = "linux")]
+mod dbus;
+mod events;
+mod focus;
+mod fullscreen;
+#[cfg(target_os = "linux")]
+mod hotkeys;
+mod input_region;
+#[cfg(target_os = "linux")]
+mod instance;
+mod interaction;
+mod monitors;
+mod overlay;
+mod rules;
+#[cfg(target_os = "linux")]
+mod socket;
+mod tray;
+
+use config::{profile_file, validation::ConfigError, AppRule, Config, ConfigStore, FullscreenAction, Hotkeys};
+#[cfg(target_os = "linux")]
+use config::HotkeyAction;
+use cursor::{CursorDriver, PointerInput, TrackingState, TrackingStatus};
+use events::{
+    ClickThroughPayload, ConfigChangedPayload, ConfigWarningPayload, IntensityPayload,
+    MonitorsChangedPayload, MouseButtonPayload, MouseMovePayload, ScrollPayload, SignalPayload,
+    TrackingStatusPayload,
+};
+
 struct AppState {
     click_through: Mutex<bool>,
+    /// Parts of the main overlay that take clicks despite click-through
+    interactive_regions: Mutex<Vec<input_region::Rect>>,
+    /// Held by open panels, which need click-through off
+    interaction: Mutex<interaction::Leases>,
     mouse_tracking: Arc<Mutex<bool>>,
+    tracking_status: Mutex<TrackingStatus>,
+    cursor_driver: Arc<CursorDriver>,
+    overlays: Mutex<Vec<overlay::Overlay>>,
+    config: Mutex<ConfigStore>,
+    /// What was done for the focused full-screen application, undone once
+    /// it loses focus
+    fullscreen: Mutex<fullscreen::FullscreenGuard>,
+    /// Per-application rules, applied as focus moves
+    rules: Mutex<rules::RuleEngine>,
+    /// Set by rules; see `IntensityPayload`
+    intensity: Mutex<f64>,
+    /// Output name of the only monitor to cover (`--monitor`)
+    only_monitor: Option<String>,
+    /// The `com.magiccursor.Overlay` object, once registered
+    #[cfg(target_os = "linux")]
+    dbus: std::sync::OnceLock<dbus::Service>,
+    /// Grabs the global shortcuts, once connected to the X server
+    #[cfg(target_os = "linux")]
+    hotkeys: std::sync::OnceLock<Arc<hotkeys::GlobalHotkeys>>,
 }
 
 #[tauri::command]
@@ -20,8 +76,131 @@ fn get_click_through_state(state: State<AppState>) -> Result<bool, String> {
         .map_err(|e| format!("Failed to get click-through state: {}", e))
 }
 
+#[tauri::command]
+fn get_tracking_status(state: State<AppState>) -> Result<TrackingStatus, String> {
+    state.tracking_status.lock()
+        .map(|guard| guard.clone())
+        .map_err(|e| format!("Failed to get tracking status: {}", e))
+}
+
+#[tauri::command]
+fn get_intensity(state: State<AppState>) -> Result<f64, String> {
+    state.intensity.lock()
+        .map(|guard| *guard)
+        .map_err(|e| format!("Failed to get intensity: {}", e))
+}
+
+#[tauri::command]
+fn get_config(state: State<AppState>) -> Result<Config, String> {
+    state.config.lock()
+        .map(|store| store.get().clone())
+        .map_err(|e| format!("Failed to get config: {}", e))
+}
+
+#[tauri::command]
+fn is_config_new(state: State<AppState>) -> Result<bool, String> {
+    state.config.lock()
+        .map(|store| store.is_new())
+        .map_err(|e| format!("Failed to get config: {}", e))
+}
+
+#[tauri::command]
+fn get_config_warning(state: State<AppState>) -> Result<Option<String>, String> {
+    state.config.lock()
+        .map(|store| store.warning().map(str::to_string))
+        .map_err(|e| format!("Failed to get config warning: {}", e))
+}
+
+#[tauri::command]
+fn set_config(app_handle: AppHandle, config: Config) -> Result<(), ConfigError> {
+    // Out-of-range settings are reported one by one, for the UI to point at
+    config.validate()?;
+    // The panels show the profile a rule uses; that doesn't make it the user's
+    let state: State<AppState> = app_handle.state();
+    let config = state.config.lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .as_saved(config);
+    update_config(&app_handle, |current| {
+        *current = config;
+        Ok(())
+    })?;
+    Ok(())
+}
+
+#[tauri::command]
+fn create_profile(app_handle: AppHandle, name: String) -> Result<(), String> {
+    update_config(&app_handle, |config| config.create_profile(&name))
+}
+
+#[tauri::command]
+fn duplicate_profile(app_handle: AppHandle, name: String, new_name: String) -> Result<(), String> {
+    update_config(&app_handle, |config| config.duplicate_profile(&name, &new_name))
+}
+
+#[tauri::command]
+fn rename_profile(app_handle: AppHandle, name: String, new_name: String) -> Result<(), String> {
+    update_config(&app_handle, |config| config.rename_profile(&name, &new_name))
+}
+
+#[tauri::command]
+fn delete_profile(app_handle: AppHandle, name: String) -> Result<(), String> {
+    update_config(&app_handle, |config| config.delete_profile(&name))
+}
+
+#[tauri::command]
+fn switch_profile(app_handle: AppHandle, name: String) -> Result<(), String> {
+    update_config(&app_handle, |config| config.switch_profile(&name))
+}
+
+#[tauri::command]
+fn set_hotkeys(app_handle: AppHandle, hotkeys: Hotkeys) -> Result<(), String> {
+    update_config(&app_handle, |config| {
+        config.hotkeys = hotkeys;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+fn export_profile(state: State<AppState>, name: String, path: PathBuf) -> Result<PathBuf, String> {
+    let profile = state.config.lock()
+        .map_err(|e| format!("Failed to lock config: {}", e))?
+        .get().profile(&name)?
+        .clone();
+    profile_file::export(&profile, &path)
+}
+
+#[tauri::command]
+fn import_profile(app_handle: AppHandle, path: PathBuf) -> Result<String, String> {
+    import_profile_file(&app_handle, &path)
+}
+
+#[tauri::command]
+fn get_monitors(window: Window) -> Result<Vec<monitors::Monitor>, String> {
+    monitors::current(&window)
+}
+
+#[tauri::command]
+fn restart_mouse_tracking(state: State<AppState>) -> Result<(), String> {
+    let source = cursor::default_source()?;
+    state.cursor_driver.replace_source(source)
+}
+
 #[tauri::command]
 fn set_click_through(window: Window, state: State<AppState>, enabled: bool) -> Result<(), String> {
+    // Open panels keep it off; the choice applies once they close
+    let deferred = state.interaction.lock()
+        .map_err(|e| format!("Failed to lock interaction leases: {}", e))?
+        .defer(enabled);
+    if deferred {
+        println!("Click-through will be {} once panels close", if enabled { "ENABLED" } else { "DISABLED" });
+        return Ok(());
+    }
+    
+    apply_click_through(&window, &state, enabled)
+}
+
+/// Switches click-through on `window`, regardless of interaction leases.
+fn apply_click_through(window: &Window, state: &AppState, enabled: bool) -> Result<(), String> {
     // Check current state
     {
         let click_through = state.click_through.lock()
@@ -88,8 +267,10 @@ fn set_click_through(window: Window, state: State<AppState>, enabled: bool) -> R
 
     #[cfg(target_os = "linux")]
     {
-        window.set_ignore_cursor_events(enabled)
-            .map_err(|e| format!("Failed to set ignore cursor events: {}", e))?;
+        let regions = state.interactive_regions.lock()
+            .map_err(|e| format!("Failed to lock interactive regions: {}", e))?
+            .clone();
+        input_region::apply(window, enabled, &regions)?;
         println!("Linux click-through set to: {}", if enabled { "ENABLED" } else { "DISABLED" });
     }
 
@@ -105,8 +286,96 @@ fn set_click_through(window: Window, state: State<AppState>, enabled: bool) -> R
         .map_err(|e| format!("Failed to update tray menu: {}", e))?;
 
     // Notify frontend
-    window.emit("click-through-changed", enabled)
+    window.emit(events::CLICK_THROUGH_CHANGED, ClickThroughPayload::new(enabled))
         .map_err(|e| format!("Failed to emit event: {}", e))?;
+    notify_state_changed(&window.app_handle());
+    
+    Ok(())
+}
+
+/// Keeps click-through off, e.g. while a panel is open, until the returned
+/// lease is released. The last release restores the user's choice.
+#[tauri::command]
+fn acquire_interaction(window: Window, state: State<AppState>) -> Result<interaction::LeaseId, String> {
+    let main_window = window.get_window(overlay::MAIN_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+    let id = {
+        let mut leases = state.interaction.lock()
+            .map_err(|e| format!("Failed to lock interaction leases: {}", e))?;
+        let click_through = *state.click_through.lock()
+            .map_err(|e| format!("Failed to lock click_through state: {}", e))?;
+        leases.acquire(window.label(), click_through)
+    };
+    
+    apply_click_through(&main_window, &state, false)?;
+    Ok(id)
+}
+
+#[tauri::command]
+fn release_interaction(app_handle: AppHandle, id: interaction::LeaseId) -> Result<(), String> {
+    let state: State<AppState> = app_handle.state();
+    let restore = state.interaction.lock()
+        .map_err(|e| format!("Failed to lock interaction leases: {}", e))?
+        .release(id)?;
+    if let Some(click_through) = restore {
+        restore_click_through(&app_handle, click_through)?;
+    }
+    Ok(())
+}
+
+/// Applies the click-through choice once the last lease is gone.
+fn restore_click_through(app_handle: &AppHandle, click_through: bool) -> Result<(), String> {
+    let window = app_handle.get_window(overlay::MAIN_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+    let state: State<AppState> = app_handle.state();
+    apply_click_through(&window, &state, click_through)
+}
+
+/// Ends the leases held by the previous page of `window`.
+fn release_window_interaction(window: &Window) {
+    let state: State<AppState> = window.state();
+    let restore = match state.interaction.lock() {
+        Ok(mut leases) => leases.release_window(window.label()),
+        Err(e) => {
+            eprintln!("Failed to lock interaction leases: {}", e);
+            return;
+        }
+    };
+    
+    if let Some(click_through) = restore {
+        println!("Released interaction leases of reloaded window {}", window.label());
+        if let Err(e) = restore_click_through(&window.app_handle(), click_through) {
+            eprintln!("Failed to restore click-through: {}", e);
+        }
+    }
+}
+
+/// Sets the parts of the main overlay that keep taking clicks while
+/// click-through is on. Only Linux can shape the input; elsewhere
+/// click-through stays all-or-nothing and the regions are ignored.
+#[tauri::command]
+fn set_interactive_regions(window: Window, state: State<AppState>, regions: Vec<input_region::Rect>) -> Result<(), String> {
+    if window.label() != overlay::MAIN_LABEL {
+        return Err("Interactive regions are only supported on the main overlay".to_string());
+    }
+    input_region::check(&regions)?;
+    
+    let click_through = *state.click_through.lock()
+        .map_err(|e| format!("Failed to lock click_through state: {}", e))?;
+    *state.interactive_regions.lock()
+        .map_err(|e| format!("Failed to lock interactive regions: {}", e))? = regions.clone();
+    
+    #[cfg(target_os = "linux")]
+    {
+        if click_through {
+            input_region::apply(&window, true, &regions)?;
+        }
+    }
+    
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = click_through;
+    }
     
     Ok(())
 }
@@ -152,8 +421,8 @@ fn request_overlay_permission(app_handle: AppHandle) {
         println!("Requesting overlay permission on Android");
         
         // Emit event to frontend to handle via Capacitor/Cordova plugin
-        if let Some(window) = app_handle.get_window("main") {
-            let _ = window.emit("request-overlay-permission", ());
+        if let Some(window) = app_handle.get_window(overlay::MAIN_LABEL) {
+            let _ = window.emit(events::REQUEST_OVERLAY_PERMISSION, SignalPayload::default());
         }
         
         // Alternative: Use JNI to directly open settings
@@ -169,6 +438,24 @@ fn request_overlay_permission(app_handle: AppHandle) {
 
 #[tauri::command]
 fn toggle_fullscreen(window: Window) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        fit_window_to_monitor(&window)?;
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        window.set_fullscreen(true)
+            .map_err(|e| format!("Failed to set fullscreen: {}", e))?;
+    }
+    notify_state_changed(&window.app_handle());
+    
+    Ok(())
+}
+
+/// Resizes the overlay to cover the whole monitor it is on.
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn fit_window_to_monitor(window: &Window) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     {
         use windows::Win32::Foundation::HWND;
@@ -208,81 +495,210 @@ fn toggle_fullscreen(window: Window) -> Result<(), String> {
 
     #[cfg(not(target_os = "windows"))]
     {
-        window.set_fullscreen(true)
-            .map_err(|e| format!("Failed to set fullscreen: {}", e))?;
+        let monitor = window.current_monitor()
+            .map_err(|e| format!("Failed to get current monitor: {}", e))?
+            .ok_or_else(|| "Window is not on any monitor".to_string())?;
+        
+        window.set_position(*monitor.position())
+            .map_err(|e| format!("Failed to set window position: {}", e))?;
+        window.set_size(*monitor.size())
+            .map_err(|e| format!("Failed to set window size: {}", e))?;
     }
     
     Ok(())
 }
 
 fn main() {
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    let options = match cli::parse(args.clone()) {
+        Ok(cli::Command::Run(options)) => options,
+        Ok(cli::Command::ListMonitors) => {
+            if let Err(e) = cli::list_monitors() {
+                eprintln!("magic-cursor: {}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        Ok(cli::Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(cli::Command::Version) => {
+            println!("magic-cursor {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(e) => {
+            eprintln!("magic-cursor: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    
+    // A second launch hands its arguments to the running instance and exits
+    #[cfg(target_os = "linux")]
+    let instance_server = match instance::claim(&args) {
+        Ok(instance::Claim::Primary(server)) => Some(server),
+        Ok(instance::Claim::Forwarded(Ok(()))) => return,
+        Ok(instance::Claim::Forwarded(Err(e))) => {
+            eprintln!("magic-cursor: {}", e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Single-instance check failed, starting anyway: {}", e);
+            None
+        }
+    };
+    
+    let context = tauri::generate_context!();
+    let config_path = options.config.clone()
+        .or_else(|| ConfigStore::default_path(context.config()))
+        .unwrap_or_else(|| config::FILE_NAME.into());
+    let mut config_store = ConfigStore::load(config_path);
+    println!("Using config file {}", config_store.path().display());
+    
+    if let Some(name) = &options.profile {
+        if let Err(e) = config_store.update(|config| config.switch_profile(name)) {
+            eprintln!("magic-cursor: {}", e);
+            std::process::exit(2);
+        }
+    }
+    
     // Create system tray menu
-    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
-    let hide = CustomMenuItem::new("hide".to_string(), "Hide");
-    let show = CustomMenuItem::new("show".to_string(), "Show");
-    let toggle_click_through_item = CustomMenuItem::new("toggle_click_through".to_string(), "Disable Click-Through");
-    let color_presets = CustomMenuItem::new("color_presets".to_string(), "Color Presets");
-    let theme_customizer = CustomMenuItem::new("theme_customizer".to_string(), "Theme Customizer");
-    let settings = CustomMenuItem::new("settings".to_string(), "Settings");
-    let welcome = CustomMenuItem::new("welcome".to_string(), "Show Welcome");
-    
-    let tray_menu = SystemTrayMenu::new()
-        .add_item(show)
-        .add_item(hide)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(toggle_click_through_item)
-        .add_item(color_presets)
-        .add_item(theme_customizer)
-        .add_item(settings)
-        .add_item(welcome)
-        .add_native_item(SystemTrayMenuItem::Separator)
-        .add_item(quit);
-
-    let system_tray = SystemTray::new().with_menu(tray_menu);
+    let system_tray = SystemTray::new().with_menu(tray::build_menu(config_store.get(), true));
     let mouse_tracking = Arc::new(Mutex::new(true));
     
     tauri::Builder::default()
         .manage(AppState {
             click_through: Mutex::new(true),
+            interactive_regions: Mutex::new(Vec::new()),
+            interaction: Mutex::new(interaction::Leases::default()),
             mouse_tracking: mouse_tracking.clone(),
+            tracking_status: Mutex::new(TrackingStatus::default()),
+            cursor_driver: Arc::new(CursorDriver::new(mouse_tracking.clone())),
+            overlays: Mutex::new(Vec::new()),
+            config: Mutex::new(config_store),
+            fullscreen: Mutex::new(fullscreen::FullscreenGuard::default()),
+            rules: Mutex::new(rules::RuleEngine::default()),
+            intensity: Mutex::new(1.0),
+            only_monitor: options.monitor.clone(),
+            #[cfg(target_os = "linux")]
+            dbus: std::sync::OnceLock::new(),
+            #[cfg(target_os = "linux")]
+            hotkeys: std::sync::OnceLock::new(),
         })
         .system_tray(system_tray)
         .on_system_tray_event(|app, event| match event {
             SystemTrayEvent::MenuItemClick { id, .. } => {
-                if let Some(window) = app.get_window("main") {
+                if let Some(window) = app.get_window(overlay::MAIN_LABEL) {
                     match id.as_str() {
                         "quit" => std::process::exit(0),
-                        "hide" => { let _ = window.hide(); }
-                        "show" => { let _ = window.show(); }
+                        "hide" => set_overlays_visible(app, false),
+                        "show" => set_overlays_visible(app, true),
                         "toggle_click_through" => toggle_click_through_fn(&window, app.state()),
-                        "color_presets" => { let _ = window.emit("open-color-presets", ()); }
-                        "theme_customizer" => { let _ = window.emit("open-theme-customizer", ()); }
-                        "settings" => { let _ = window.emit("open-settings", ()); }
-                        "welcome" => { let _ = window.emit("open-welcome", ()); }
-                        _ => {}
+                        "color_presets" => { let _ = window.emit(events::OPEN_COLOR_PRESETS, SignalPayload::default()); }
+                        "theme_customizer" => { let _ = window.emit(events::OPEN_THEME_CUSTOMIZER, SignalPayload::default()); }
+                        "settings" => { let _ = window.emit(events::OPEN_SETTINGS, SignalPayload::default()); }
+                        "welcome" => { let _ = window.emit(events::OPEN_WELCOME, SignalPayload::default()); }
+                        id => {
+                            if let Some(name) = tray::profile_for_item(id) {
+                                if let Err(e) = update_config(app, |config| config.switch_profile(name)) {
+                                    eprintln!("Failed to switch profile: {}", e);
+                                }
+                            }
+                        }
                     }
                 }
             }
             _ => {}
         })
+        // A reloaded or crashed page can't release its leases itself
+        .on_page_load(|window, _| release_window_interaction(&window))
         .invoke_handler(tauri::generate_handler![
             get_click_through_state, 
             set_click_through, 
+            set_interactive_regions,
+            acquire_interaction,
+            release_interaction,
+            get_tracking_status,
+            get_intensity,
+            get_config,
+            set_config,
+            is_config_new,
+            get_config_warning,
+            create_profile,
+            duplicate_profile,
+            rename_profile,
+            delete_profile,
+            switch_profile,
+            set_hotkeys,
+            export_profile,
+            import_profile,
+            restart_mouse_tracking,
+            get_monitors,
             toggle_fullscreen,
             check_overlay_permission,
             request_overlay_permission
         ])
-        .setup(|app| {
-            let window = app.get_window("main").unwrap();
+        .setup(move |app| {
+            let window = app.get_window(overlay::MAIN_LABEL).unwrap();
             let app_handle = app.handle();
             let state: State<AppState> = app.state();
-            let mouse_tracking = state.mouse_tracking.clone();
+            let cursor_driver = state.cursor_driver.clone();
+            
+            // Apply edits made to the config file while the app runs
+            let config_path = state.config.lock()
+                .map(|store| store.path().to_path_buf())
+                .map_err(|e| format!("Failed to lock config: {}", e))?;
+            let config_handle = app_handle.clone();
+            thread::spawn(move || {
+                let result = config::watch(&config_path, || on_config_file_changed(&config_handle));
+                if let Err(e) = result {
+                    eprintln!("Config hot-reload disabled: {}", e);
+                }
+            });
+            
+            // Profiles opened through the .magiccursor file association
+            for path in &options.import {
+                if let Err(e) = import_profile_file(&app_handle, path) {
+                    report_import_error(&app_handle, e);
+                }
+            }
             
             // Start global mouse tracking thread
             #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
             {
+                let tracking_handle = app_handle.clone();
+                thread::spawn(move || {
+                    start_mouse_tracking(tracking_handle, cursor_driver);
+                });
+                
+                // Step aside for full-screen games, videos and presentations
+                let focus_handle = app_handle.clone();
                 thread::spawn(move || {
-                    start_mouse_tracking(app_handle, mouse_tracking);
+                    let result = focus::default_source()
+                        .and_then(|source| focus::watch(source, |focused| on_focus_changed(&focus_handle, focused)));
+                    if let Err(e) = result {
+                        println!("Full-screen detection disabled: {}", e);
+                    }
+                });
+                
+                // Cover every monitor, then follow hotplug and resolution changes
+                let overlay_window = window.clone();
+                thread::spawn(move || {
+                    let state: State<AppState> = app_handle.state();
+                    match monitors::current(&overlay_window) {
+                        Ok(layout) => {
+                            let covered = covered_monitors(&app_handle, &layout);
+                            if let Err(e) = overlay::sync(&app_handle, &state.overlays, &covered) {
+                                eprintln!("Failed to open overlay windows: {}", e);
+                            }
+                        }
+                        Err(e) => eprintln!("Failed to list monitors: {}", e),
+                    }
+                    
+                    let result = monitors::watch(|layout| on_monitors_changed(&app_handle, layout));
+                    if let Err(e) = result {
+                        println!("Monitor change detection disabled: {}", e);
+                    }
                 });
             }
             
@@ -331,239 +747,646 @@ fn main() {
                 }
             }
 
+            // Start in the active profile's click-through state
+            let mut click_through = !options.no_click_through && state.config.lock().ok()
+                .and_then(|store| store.get().active().map(|profile| profile.behavior.click_through))
+                .unwrap_or(true);
+            if options.toggle_click_through {
+                click_through = !click_through;
+            }
+            if !click_through {
+                set_click_through(window.clone(), app.state(), false)?;
+            }
+            
+            if options.start_hidden {
+                window.hide().map_err(|e| format!("Failed to hide window: {}", e))?;
+            }
+            
+            // Later launches forward their arguments here, and scripts use
+            // the control socket. Neither is safe to open without the lock.
+            #[cfg(target_os = "linux")]
+            {
+                if let Some(server) = instance_server {
+                    let launch_handle = app.handle();
+                    thread::spawn(move || {
+                        server.serve(|args, cwd| on_second_launch(&launch_handle, args, cwd));
+                    });
+                    
+                    // The D-Bus object runs the same methods as the socket
+                    let control_handle = app.handle();
+                    let handler: Arc<control::Handler> = Arc::new(move |method: &str, params: &serde_json::Map<String, serde_json::Value>| {
+                        on_control_request(&control_handle, method, params)
+                    });
+                    match dbus::start(handler.clone()) {
+                        Ok(service) => {
+                            let _ = app.state::<AppState>().dbus.set(service);
+                        }
+                        Err(e) => eprintln!("D-Bus service disabled: {}", e),
+                    }
+                    thread::spawn(move || {
+                        if let Err(e) = control::serve(handler) {
+                            eprintln!("Control socket disabled: {}", e);
+                        }
+                    });
+                }
+            }
+            
+            // Keyboard control while click-through keeps the overlay unfocused
+            #[cfg(target_os = "linux")]
+            {
+                match hotkeys::GlobalHotkeys::connect(None) {
+                    Ok(grabber) => {
+                        let grabber = Arc::new(grabber);
+                        let _ = app.state::<AppState>().hotkeys.set(grabber.clone());
+                        let hotkeys = app.state::<AppState>().config.lock()
+                            .map(|store| store.get().hotkeys.clone())
+                            .unwrap_or_default();
+                        apply_hotkeys(&app.handle(), &hotkeys);
+                        
+                        let hotkey_handle = app.handle();
+                        thread::spawn(move || {
+                            if let Err(e) = grabber.run(|action| on_hotkey(&hotkey_handle, action)) {
+                                eprintln!("Global shortcuts stopped: {}", e);
+                            }
+                        });
+                    }
+                    Err(e) => eprintln!("Global shortcuts disabled: {}", e),
+                }
+            }
+
             Ok(())
         })
-        .run(tauri::generate_context!())
+        .run(context)
         .expect("error while running tauri application");
 }
 
-#[cfg(target_os = "windows")]
-fn start_mouse_tracking(app_handle: AppHandle, tracking_enabled: Arc<Mutex<bool>>) {
-    use windows::Win32::UI::WindowsAndMessaging::{GetCursorPos, GetSystemMetrics, SM_CXSCREEN, SM_CYSCREEN};
-    use windows::Win32::Foundation::POINT;
-    use std::time::Duration;
-    
-    let (screen_width, screen_height) = unsafe {
-        (GetSystemMetrics(SM_CXSCREEN), GetSystemMetrics(SM_CYSCREEN))
-    };
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn start_mouse_tracking(app_handle: AppHandle, driver: Arc<CursorDriver>) {
+    match cursor::default_source() {
+        Ok(source) => {
+            let _ = driver.replace_source(source);
+        }
+        Err(e) => {
+            report_tracking_status(&app_handle, TrackingStatus::new("none", TrackingState::Unavailable, Some(e)));
+        }
+    }
     
-    loop {
-        thread::sleep(Duration::from_millis(16));
-        
-        let is_tracking = match tracking_enabled.lock() {
-            Ok(guard) => *guard,
-            Err(_) => {
-                thread::sleep(Duration::from_millis(100));
-                continue;
+    let state: State<AppState> = app_handle.state();
+    let screen = Cell::new((0, 0));
+    let status_handle = app_handle.clone();
+    driver.run(
+        move |status| report_tracking_status(&status_handle, status),
+        |sample| {
+            screen.set((sample.screen_width, sample.screen_height));
+            if let Some(target) = overlay::target_at(&app_handle, &state.overlays, sample.x, sample.y, screen.get()) {
+                let payload = MouseMovePayload::new(target.point, target.width, target.height);
+                let _ = target.window.emit(events::MOUSE_MOVE, payload);
             }
-        };
-        
-        if !is_tracking {
-            thread::sleep(Duration::from_millis(100));
-            continue;
-        }
+        },
+        |input| {
+            let (x, y) = match input {
+                PointerInput::Button { x, y, .. } | PointerInput::Scroll { x, y, .. } => (x, y),
+            };
+            if let Some(target) = overlay::target_at(&app_handle, &state.overlays, x, y, screen.get()) {
+                let _ = match input {
+                    PointerInput::Button { button, pressed, modifiers, .. } => target.window.emit(
+                        if pressed { events::MOUSE_DOWN } else { events::MOUSE_UP },
+                        MouseButtonPayload::new(target.point, button, modifiers),
+                    ),
+                    PointerInput::Scroll { delta_x, delta_y, modifiers, .. } => target.window.emit(
+                        events::SCROLL,
+                        ScrollPayload::new(target.point, delta_x, delta_y, modifiers),
+                    ),
+                };
+            }
+        },
+    );
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn on_monitors_changed(app_handle: &AppHandle, mut layout: Vec<monitors::Monitor>) {
+    println!("Monitor layout changed: {} monitor(s)", layout.len());
+    
+    let state: State<AppState> = app_handle.state();
+    state.cursor_driver.refresh_screen();
+    
+    // Fullscreen windows are resized by the window manager
+    if let Some(window) = app_handle.get_window(overlay::MAIN_LABEL) {
+        monitors::apply_scale_factors(&mut layout, &window);
         
-        unsafe {
-            let mut point = POINT { x: 0, y: 0 };
-            if GetCursorPos(&mut point).is_ok() {
-                if let Some(window) = app_handle.get_window("main") {
-                    let _ = window.emit("global-mouse-move", serde_json::json!({
-                        "x": point.x,
-                        "y": point.y,
-                        "screenWidth": screen_width,
-                        "screenHeight": screen_height,
-                    }));
-                }
+        if !window.is_fullscreen().unwrap_or(false) {
+            if let Err(e) = fit_window_to_monitor(&window) {
+                eprintln!("Failed to refit overlay window: {}", e);
             }
         }
     }
+    
+    if let Err(e) = overlay::sync(app_handle, &state.overlays, &covered_monitors(app_handle, &layout)) {
+        eprintln!("Failed to update overlay windows: {}", e);
+    }
+    
+    let _ = app_handle.emit_all(events::MONITORS_CHANGED, MonitorsChangedPayload::new(layout));
 }
 
-#[cfg(target_os = "linux")]
-fn start_mouse_tracking(app_handle: AppHandle, tracking_enabled: Arc<Mutex<bool>>) {
-    use std::time::Duration;
-    use std::process::Command;
-    
-    let has_xdotool = Command::new("which")
-        .arg("xdotool")
-        .output()
-        .map(|o| o.status.success())
-        .unwrap_or(false);
+fn set_overlays_visible(app_handle: &AppHandle, visible: bool) {
+    // Showing or hiding by hand ends hiding for a full-screen application
+    let state: State<AppState> = app_handle.state();
+    let effects = state.fullscreen.lock()
+        .map(|mut guard| guard.overridden(FullscreenAction::Hide))
+        .unwrap_or_default();
+    apply_fullscreen_effects(app_handle, effects);
     
-    if !has_xdotool {
-        eprintln!("Warning: xdotool not found. Mouse tracking disabled.");
-        eprintln!("Install with: sudo apt-get install xdotool");
-        return;
+    for window in app_handle.windows().values() {
+        let _ = if visible { window.show() } else { window.hide() };
+    }
+    notify_state_changed(app_handle);
+}
+
+/// Pauses or resumes following the cursor.
+#[cfg(target_os = "linux")]
+fn set_tracking_paused(app_handle: &AppHandle, paused: bool) {
+    // Like showing and hiding, this overrides the full-screen and rule pauses
+    let state: State<AppState> = app_handle.state();
+    let effects = state.fullscreen.lock()
+        .map(|mut guard| guard.overridden(FullscreenAction::Pause))
+        .unwrap_or_default();
+    apply_fullscreen_effects(app_handle, effects);
+    if let Ok(mut engine) = state.rules.lock() {
+        engine.forget_pause();
     }
     
-    let (mut screen_width, mut screen_height) = (1920, 1080);
-    if let Ok(output) = Command::new("xdpyinfo").output() {
-        if let Ok(info) = String::from_utf8(output.stdout) {
-            for line in info.lines() {
-                if line.contains("dimensions:") {
-                    if let Some(dims) = line.split_whitespace().nth(1) {
-                        if let Some((w, h)) = dims.split_once('x') {
-                            screen_width = w.parse().unwrap_or(1920);
-                            screen_height = h.parse().unwrap_or(1080);
-                        }
-                    }
-                    break;
-                }
-            }
+    state.cursor_driver.set_paused(paused);
+    notify_state_changed(app_handle);
+}
+
+/// Applies the rules for the newly focused application, then pauses or
+/// hides the overlay under a focused full-screen application, as the config
+/// says, and undoes it once that has lost focus.
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn on_focus_changed(app_handle: &AppHandle, focused: Option<focus::FocusedWindow>) {
+    let state: State<AppState> = app_handle.state();
+    let fullscreen_at = focused.as_ref()
+        .filter(|window| window.fullscreen && !window.is_ours())
+        .map(|window| window.center);
+    // Full-screen pauses are per overlay, so rules only see their own and the user's
+    run_rules(app_handle, |engine, rules, active_profile, paused| {
+        engine.focus_changed(focused, rules, active_profile, paused)
+    });
+    
+    let action = match state.config.lock() {
+        Ok(store) => store.get().fullscreen_app,
+        Err(e) => {
+            eprintln!("Failed to lock config: {}", e);
+            return;
         }
-    }
+    };
+    // Nothing is covered when the application is on a monitor without overlay
+    let covered = match (fullscreen_at, state.overlays.lock()) {
+        (Some(center), Ok(overlays)) => overlay::covering(&overlays, center).map(str::to_string),
+        (None, _) => None,
+        (Some(_), Err(e)) => {
+            eprintln!("Failed to lock overlays: {}", e);
+            return;
+        }
+    };
+    let visible = app_handle.get_window(overlay::MAIN_LABEL)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
     
-    loop {
-        thread::sleep(Duration::from_millis(16));
-        
-        let is_tracking = match tracking_enabled.lock() {
-            Ok(guard) => *guard,
-            Err(_) => {
-                thread::sleep(Duration::from_millis(100));
-                continue;
-            }
-        };
-        
-        if !is_tracking {
-            thread::sleep(Duration::from_millis(100));
-            continue;
+    let effects = match state.fullscreen.lock() {
+        Ok(mut guard) => guard.focus_changed(covered.as_deref(), action, state.cursor_driver.is_paused(), visible),
+        Err(e) => {
+            eprintln!("Failed to lock full-screen state: {}", e);
+            return;
         }
-        
-        if let Ok(output) = Command::new("xdotool").args(&["getmouselocation", "--shell"]).output() {
-            if let Ok(result) = String::from_utf8(output.stdout) {
-                let (mut x, mut y) = (0, 0);
-                
-                for line in result.lines() {
-                    if let Some((key, value)) = line.split_once('=') {
-                        match key {
-                            "X" => x = value.parse().unwrap_or(0),
-                            "Y" => y = value.parse().unwrap_or(0),
-                            _ => {}
-                        }
+    };
+    apply_fullscreen_effects(app_handle, effects);
+}
+
+/// Carries out what the full-screen guard decided for single overlays.
+fn apply_fullscreen_effects(app_handle: &AppHandle, effects: Vec<fullscreen::Effect>) {
+    let state: State<AppState> = app_handle.state();
+    for effect in effects {
+        println!("Full-screen application: {:?}", effect);
+        match effect {
+            fullscreen::Effect::SetPaused { overlay, paused } => {
+                if let Ok(mut overlays) = state.overlays.lock() {
+                    if let Some(overlay) = overlays.iter_mut().find(|candidate| candidate.label == overlay) {
+                        overlay.paused = paused;
                     }
                 }
-                
-                if let Some(window) = app_handle.get_window("main") {
-                    let _ = window.emit("global-mouse-move", serde_json::json!({
-                        "x": x,
-                        "y": y,
-                        "screenWidth": screen_width,
-                        "screenHeight": screen_height,
-                    }));
+            }
+            fullscreen::Effect::SetVisible { overlay, visible } => {
+                if let Some(window) = app_handle.get_window(&overlay) {
+                    let _ = if visible { window.show() } else { window.hide() };
                 }
+                notify_state_changed(app_handle);
             }
         }
     }
 }
 
-#[cfg(target_os = "macos")]
-fn start_mouse_tracking(app_handle: AppHandle, tracking_enabled: Arc<Mutex<bool>>) {
-    use std::time::Duration;
-    use cocoa::appkit::NSEvent;
-    use cocoa::foundation::NSPoint;
+/// Lets the rules engine decide with the current config and state, then
+/// carries out what it decided.
+fn run_rules<F>(app_handle: &AppHandle, decide: F)
+where
+    F: FnOnce(&mut rules::RuleEngine, &[AppRule], &str, bool) -> Vec<rules::Effect>,
+{
+    let state: State<AppState> = app_handle.state();
+    let (rules, active_profile) = match state.config.lock() {
+        Ok(store) => (store.get().rules.clone(), store.get().active_profile.clone()),
+        Err(e) => {
+            eprintln!("Failed to lock config: {}", e);
+            return;
+        }
+    };
+    let effects = match state.rules.lock() {
+        Ok(mut engine) => decide(&mut engine, &rules, &active_profile, state.cursor_driver.is_paused()),
+        Err(e) => {
+            eprintln!("Failed to lock rules: {}", e);
+            return;
+        }
+    };
     
-    loop {
-        thread::sleep(Duration::from_millis(16));
-        
-        let is_tracking = match tracking_enabled.lock() {
-            Ok(guard) => *guard,
-            Err(_) => {
-                thread::sleep(Duration::from_millis(100));
-                continue;
+    for effect in effects {
+        println!("Application rule: {:?}", effect);
+        match effect {
+            rules::Effect::SetPaused(paused) => {
+                state.cursor_driver.set_paused(paused);
+                notify_state_changed(app_handle);
             }
-        };
-        
-        if !is_tracking {
-            thread::sleep(Duration::from_millis(100));
-            continue;
-        }
-        
-        unsafe {
-            let mouse_location: NSPoint = NSEvent::mouseLocation();
-            let screen_width = 1920;
-            let screen_height = 1080;
-            
-            if let Some(window) = app_handle.get_window("main") {
-                let _ = window.emit("global-mouse-move", serde_json::json!({
-                    "x": mouse_location.x as i32,
-                    "y": (screen_height as f64 - mouse_location.y) as i32,
-                    "screenWidth": screen_width,
-                    "screenHeight": screen_height,
-                }));
+            rules::Effect::SwitchProfile(name) => use_profile(app_handle, Some(&name)),
+            rules::Effect::RestoreProfile => use_profile(app_handle, None),
+            rules::Effect::SetIntensity(intensity) => {
+                if let Ok(mut current) = state.intensity.lock() {
+                    *current = intensity;
+                }
+                let _ = app_handle.emit_all(events::INTENSITY_CHANGED, IntensityPayload::new(intensity));
             }
         }
     }
 }
 
-fn toggle_click_through_fn(window: &Window, state: State<AppState>) {
-    let is_enabled = {
-        let mut click_through = match state.click_through.lock() {
-            Ok(guard) => guard,
-            Err(e) => {
-                eprintln!("Failed to lock click_through: {}", e);
-                return;
-            }
-        };
-        *click_through = !*click_through;
-        *click_through
+/// Uses the profile `name` for a rule, or the user's own again with `None`.
+///
+/// Nothing is saved, and the profile's click-through is left as it is.
+fn use_profile(app_handle: &AppHandle, name: Option<&str>) {
+    let state: State<AppState> = app_handle.state();
+    let result = match state.config.lock() {
+        Ok(mut store) => store.use_profile(name).map(|changed| changed.then(|| store.get().clone())),
+        Err(e) => Err(format!("Failed to lock config: {}", e)),
     };
+    
+    match result {
+        Ok(Some(config)) => {
+            let click_through = state.click_through.lock().map(|guard| *guard).unwrap_or(true);
+            tray::refresh(app_handle, &config, click_through);
+            let _ = app_handle.emit_all(events::CONFIG_CHANGED, ConfigChangedPayload::new(config));
+            notify_state_changed(app_handle);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Rule could not switch profile: {}", e),
+    }
+}
 
-    println!("Toggle click-through: {}", is_enabled);
+/// Grabs the shortcuts in `hotkeys` in place of the previous ones.
+#[cfg(target_os = "linux")]
+fn apply_hotkeys(app_handle: &AppHandle, hotkeys: &Hotkeys) {
+    let state: State<AppState> = app_handle.state();
+    let Some(grabber) = state.hotkeys.get() else {
+        return;
+    };
+    
+    // Saved configs only hold valid shortcuts
+    let (bindings, _) = hotkeys.bindings();
+    let problems = grabber.bind(&bindings);
+    if !problems.is_empty() {
+        show_warning(app_handle, format!("Some shortcuts are unavailable: {}.", problems.join("; ")));
+    }
+}
 
-    {
-        let mut tracking = match state.mouse_tracking.lock() {
-            Ok(guard) => guard,
-            Err(e) => {
-                eprintln!("Failed to lock mouse_tracking: {}", e);
-                return;
+/// Runs the action of a global shortcut.
+#[cfg(target_os = "linux")]
+fn on_hotkey(app_handle: &AppHandle, action: HotkeyAction) {
+    println!("Shortcut pressed: {}", action.description());
+    let state: State<AppState> = app_handle.state();
+    let Some(window) = app_handle.get_window(overlay::MAIN_LABEL) else {
+        return;
+    };
+    
+    match action {
+        HotkeyAction::ToggleClickThrough => toggle_click_through_fn(&window, state),
+        HotkeyAction::ToggleTracking => set_tracking_paused(app_handle, !state.cursor_driver.is_paused()),
+        HotkeyAction::NextProfile => {
+            let result = update_config(app_handle, |config| {
+                config.switch_to_next_profile();
+                Ok(())
+            });
+            if let Err(e) = result {
+                eprintln!("Failed to switch profile: {}", e);
             }
-        };
-        *tracking = is_enabled;
+        }
+        HotkeyAction::ToggleVisible => set_overlays_visible(app_handle, !window.is_visible().unwrap_or(false)),
+    }
+}
+
+/// Lets D-Bus clients know that something `get_state` reports may have changed.
+fn notify_state_changed(app_handle: &AppHandle) {
+    #[cfg(target_os = "linux")]
+    {
+        let state: State<AppState> = app_handle.state();
+        if let Some(service) = state.dbus.get() {
+            service.refresh();
+        }
     }
     
-    #[cfg(target_os = "windows")]
+    #[cfg(not(target_os = "linux"))]
     {
-        use windows::Win32::Foundation::HWND;
-        use windows::Win32::UI::WindowsAndMessaging::{
-            GetWindowLongPtrW, SetWindowLongPtrW, GWL_EXSTYLE, WS_EX_TRANSPARENT, WS_EX_LAYERED,
-        };
+        let _ = app_handle;
+    }
+}
 
-        if let Ok(hwnd_wrapper) = window.hwnd() {
-            let hwnd = HWND(hwnd_wrapper.0 as isize);
-            unsafe {
-                let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
-                let new_style = if is_enabled {
-                    ex_style | WS_EX_LAYERED.0 as isize | WS_EX_TRANSPARENT.0 as isize
-                } else {
-                    ex_style & !(WS_EX_TRANSPARENT.0 as isize)
-                };
-                
-                SetWindowLongPtrW(hwnd, GWL_EXSTYLE, new_style);
-                println!("Click-through: {}", if is_enabled { "ENABLED" } else { "DISABLED" });
+/// Applies the options of a later launch, which exits once this returns.
+#[cfg(target_os = "linux")]
+fn on_second_launch(app_handle: &AppHandle, args: Vec<OsString>, cwd: PathBuf) -> Result<(), String> {
+    let options = match cli::parse(args)? {
+        cli::Command::Run(options) => options,
+        // Answered by the launching process itself
+        _ => return Ok(()),
+    };
+    
+    // A plain relaunch brings the overlay back
+    if options == cli::Options::default() {
+        set_overlays_visible(app_handle, true);
+        return Ok(());
+    }
+    
+    let mut errors = Vec::new();
+    if options.config.is_some() || options.monitor.is_some() {
+        errors.push("--config and --monitor only apply when the app starts".to_string());
+    }
+    if let Some(name) = &options.profile {
+        if let Err(e) = update_config(app_handle, |config| config.switch_profile(name)) {
+            errors.push(e);
+        }
+    }
+    for path in &options.import {
+        if let Err(e) = import_profile_file(app_handle, &cwd.join(path)) {
+            // Opened from a file manager, nobody reads the launcher's output
+            report_import_error(app_handle, e.clone());
+            errors.push(e);
+        }
+    }
+    
+    if let Some(window) = app_handle.get_window(overlay::MAIN_LABEL) {
+        if options.no_click_through {
+            if let Err(e) = set_click_through(window.clone(), app_handle.state(), false) {
+                errors.push(e);
             }
         }
+        if options.toggle_click_through {
+            toggle_click_through_fn(&window, app_handle.state());
+        }
+    }
+    if options.start_hidden {
+        set_overlays_visible(app_handle, false);
     }
+    
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
 
-    #[cfg(target_os = "macos")]
-    {
-        use cocoa::appkit::NSWindow;
-        use cocoa::base::id;
+/// Runs one control socket method. They mirror the tray items, plus
+/// `get_state`.
+#[cfg(target_os = "linux")]
+fn on_control_request(
+    app_handle: &AppHandle,
+    method: &str,
+    params: &serde_json::Map<String, serde_json::Value>,
+) -> Result<serde_json::Value, control::RpcError> {
+    let state: State<AppState> = app_handle.state();
+    let window = app_handle.get_window(overlay::MAIN_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+    let open_panel = |event: &str| {
+        window.emit(event, SignalPayload::default())
+            .map_err(|e| format!("Failed to emit event: {}", e))
+    };
+    
+    match method {
+        "show" => set_overlays_visible(app_handle, true),
+        "hide" => set_overlays_visible(app_handle, false),
+        "toggle_click_through" => toggle_click_through_fn(&window, state),
+        "toggle_fullscreen" => toggle_fullscreen(window.clone())?,
+        "set_click_through" => set_click_through(window.clone(), state, control::param(params, "enabled")?)?,
+        "open_color_presets" => open_panel(events::OPEN_COLOR_PRESETS)?,
+        "open_theme_customizer" => open_panel(events::OPEN_THEME_CUSTOMIZER)?,
+        "open_settings" => open_panel(events::OPEN_SETTINGS)?,
+        "open_welcome" => open_panel(events::OPEN_WELCOME)?,
+        "switch_profile" => {
+            let name: String = control::param(params, "name")?;
+            update_config(app_handle, |config| config.switch_profile(&name))?;
+        }
+        "pause_tracking" => set_tracking_paused(app_handle, true),
+        "resume_tracking" => set_tracking_paused(app_handle, false),
+        "quit" => app_handle.exit(0),
+        "get_state" => {
+            let click_through = *state.click_through.lock()
+                .map_err(|e| format!("Failed to get click-through state: {}", e))?;
+            let tracking = state.tracking_status.lock()
+                .map_err(|e| format!("Failed to get tracking status: {}", e))?
+                .clone();
+            let config = state.config.lock()
+                .map_err(|e| format!("Failed to get config: {}", e))?
+                .get()
+                .clone();
+            
+            return Ok(serde_json::json!({
+                "visible": window.is_visible().unwrap_or(false),
+                "fullscreen": window.is_fullscreen().unwrap_or(false),
+                "clickThrough": click_through,
+                "trackingPaused": state.cursor_driver.is_paused(),
+                "tracking": tracking,
+                "activeProfile": config.active_profile,
+                "profiles": config.profiles.iter().map(|profile| &profile.name).collect::<Vec<_>>(),
+            }));
+        }
+        _ => return Err(control::RpcError::method_not_found(method)),
+    }
+    
+    Ok(serde_json::Value::Null)
+}
 
-        if let Ok(ns_window_ptr) = window.ns_window() {
-            let ns_window = ns_window_ptr as id;
-            unsafe {
-                ns_window.setIgnoresMouseEvents_(is_enabled);
-            }
+/// The monitors to put overlays on: only the `--monitor` one while it is
+/// connected, otherwise all of them.
+fn covered_monitors(app_handle: &AppHandle, layout: &[monitors::Monitor]) -> Vec<monitors::Monitor> {
+    let state: State<AppState> = app_handle.state();
+    let Some(id) = &state.only_monitor else {
+        return layout.to_vec();
+    };
+    
+    match layout.iter().find(|monitor| &monitor.id == id) {
+        Some(monitor) => vec![monitor.clone()],
+        None => {
+            eprintln!("Monitor {} is not connected, covering all monitors", id);
+            layout.to_vec()
         }
     }
+}
 
+/// Applies `change` to the stored config and tells every window about it.
+fn update_config<F>(app_handle: &AppHandle, change: F) -> Result<(), String>
+where
+    F: FnOnce(&mut Config) -> Result<(), String>,
+{
+    let state: State<AppState> = app_handle.state();
+    let (previous, config) = {
+        let mut store = state.config.lock()
+            .map_err(|e| format!("Failed to lock config: {}", e))?;
+        let previous = store.get().clone();
+        if !store.update(change)? {
+            return Ok(());
+        }
+        (previous, store.get().clone())
+    };
+    
+    on_config_changed(app_handle, &previous, config);
+    Ok(())
+}
+
+fn on_config_changed(app_handle: &AppHandle, previous: &Config, config: Config) {
+    let state: State<AppState> = app_handle.state();
+    
+    // Switching profiles also switches to the profile's click-through state
+    if previous.active_profile != config.active_profile {
+        if let (Some(profile), Some(window)) = (config.active(), app_handle.get_window(overlay::MAIN_LABEL)) {
+            if let Err(e) = set_click_through(window, app_handle.state(), profile.behavior.click_through) {
+                eprintln!("Failed to apply profile click-through: {}", e);
+            }
+        }
+    }
+    
+    let click_through = state.click_through.lock().map(|guard| *guard).unwrap_or(true);
+    tray::refresh(app_handle, &config, click_through);
+    
     #[cfg(target_os = "linux")]
     {
-        let _ = window.set_ignore_cursor_events(is_enabled);
+        if previous.hotkeys != config.hotkeys {
+            apply_hotkeys(app_handle, &config.hotkeys);
+        }
+    }
+    
+    let rules_changed = previous.rules != config.rules;
+    // Every window renders from the config, not just the one that changed it
+    let _ = app_handle.emit_all(events::CONFIG_CHANGED, ConfigChangedPayload::new(config));
+    notify_state_changed(app_handle);
+    
+    if rules_changed {
+        run_rules(app_handle, |engine, rules, active_profile, paused| engine.update(rules, active_profile, paused));
     }
+}
 
-    let _ = window.app_handle().tray_handle().get_item("toggle_click_through")
-        .set_title(if is_enabled { "Disable Click-Through" } else { "Enable Click-Through" });
+fn on_config_file_changed(app_handle: &AppHandle) {
+    let state: State<AppState> = app_handle.state();
+    let result = match state.config.lock() {
+        Ok(mut store) => {
+            let previous = store.get().clone();
+            store.reload().map(|changed| changed.then(|| (previous, store.get().clone())))
+        }
+        Err(e) => Err(format!("Failed to lock config: {}", e)),
+    };
+    
+    match result {
+        Ok(Some((previous, config))) => {
+            println!("Config file changed on disk, reloaded");
+            on_config_changed(app_handle, &previous, config);
+        }
+        Ok(None) => {}
+        Err(warning) => {
+            eprintln!("Warning: {}", warning);
+            let _ = app_handle.emit_all(events::CONFIG_WARNING, ConfigWarningPayload::new(warning));
+        }
+    }
+}
 
-    let _ = window.emit("click-through-changed", is_enabled);
+/// Adds the profile in a `.magiccursor` file and switches to it.
+fn import_profile_file(app_handle: &AppHandle, path: &Path) -> Result<String, String> {
+    let profile = profile_file::read(path)?;
+    let mut name = String::new();
+    update_config(app_handle, |config| {
+        name = config.import_profile(profile);
+        config.switch_profile(&name)
+    })?;
+    
+    println!("Imported profile '{}' from {}", name, path.display());
+    Ok(name)
+}
+
+fn report_import_error(app_handle: &AppHandle, error: String) {
+    show_warning(app_handle, format!("Could not import profile: {}", error));
+}
+
+/// Keeps the warning for windows that haven't loaded yet and shows it in the others.
+fn show_warning(app_handle: &AppHandle, warning: String) {
+    eprintln!("Warning: {}", warning);
+    
+    let state: State<AppState> = app_handle.state();
+    if let Ok(mut store) = state.config.lock() {
+        store.warn(warning.clone());
+    }
+    let _ = app_handle.emit_all(events::CONFIG_WARNING, ConfigWarningPayload::new(warning));
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
+fn report_tracking_status(app_handle: &AppHandle, status: TrackingStatus) {
+    match status.state {
+        TrackingState::Unavailable | TrackingState::Degraded => eprintln!(
+            "Mouse tracking {:?} ({}): {}",
+            status.state,
+            status.backend,
+            status.message.as_deref().unwrap_or("no details")
+        ),
+        _ => println!("Mouse tracking backend: {}", status.backend),
+    }
+
+    let state: State<AppState> = app_handle.state();
+    if let Ok(mut current) = state.tracking_status.lock() {
+        *current = status.clone();
+    }
+
+    let _ = app_handle.emit_all(events::TRACKING_STATUS_CHANGED, TrackingStatusPayload::new(status));
+    notify_state_changed(app_handle);
+}
+
+fn toggle_click_through_fn(window: &Window, state: State<AppState>) {
+    // Open panels keep it off; flip the choice applied once they close
+    {
+        let mut leases = match state.interaction.lock() {
+            Ok(guard) => guard,
+            Err(e) => {
+                eprintln!("Failed to lock interaction leases: {}", e);
+                return;
+            }
+        };
+        if let Some(preference) = leases.deferred() {
+            leases.defer(!preference);
+            println!("Click-through will be {} once panels close", if preference { "DISABLED" } else { "ENABLED" });
+            return;
+        }
+    }
+    
+    let enabled = match state.click_through.lock() {
+        Ok(click_through) => !*click_through,
+        Err(e) => {
+            eprintln!("Failed to lock click_through: {}", e);
+            return;
+        }
+    };
+
+    println!("Toggle click-through: {}", enabled);
+    if let Err(e) = apply_click_through(window, &state, enabled) {
+        eprintln!("Failed to toggle click-through: {}", e);
+    }
 }
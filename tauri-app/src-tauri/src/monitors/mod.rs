@@ -0,0 +1,173 @@
+// Monitor layout discovery
+
+use serde::Serialize;
+use ts_rs::TS;
+
+#[cfg(target_os = "linux")]
+pub mod x11;
+
+/// One active display output, in the global desktop coordinate space.
+#[derive(Clone, Debug, PartialEq, Serialize, TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export)]
+pub struct Monitor {
+    /// Output name (e.g. `DP-1`), stable across reconnects
+    pub id: String,
+    pub x: i32,
+    pub y: i32,
+    /// Width in physical pixels, after rotation
+    pub width: u32,
+    /// Height in physical pixels, after rotation
+    pub height: u32,
+    /// Clockwise rotation in degrees: 0, 90, 180 or 270
+    pub rotation: u16,
+    pub primary: bool,
+    /// Physical pixels per logical (CSS) pixel, as used by the webview
+    pub scale_factor: f64,
+}
+
+/// Lists the active monitors using the best native API available.
+///
+/// Returns `None` when no native backend applies, in which case callers
+/// should fall back to `from_tauri`.
+pub fn enumerate() -> Option<Result<Vec<Monitor>, String>> {
+    #[cfg(target_os = "linux")]
+    {
+        match x11::X11Monitors::connect(None) {
+            Ok(monitors) => Some(monitors.list()),
+            Err(e) => {
+                println!("XRandR unavailable, using window system monitors: {}", e);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+/// Blocks, calling `on_change` with the new layout whenever a monitor is
+/// connected, removed, moved, rotated or changes resolution.
+///
+/// Returns an error if change notifications aren't available on this
+/// system or the display connection is lost.
+pub fn watch<F: FnMut(Vec<Monitor>)>(mut on_change: F) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let monitors = x11::X11Monitors::connect(None)?;
+        monitors.select_changes()?;
+
+        let mut current = monitors.list()?;
+        loop {
+            monitors.wait_for_change()?;
+
+            let layout = monitors.list()?;
+            if layout != current {
+                current = layout.clone();
+                on_change(layout);
+            }
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = &mut on_change;
+        Err("Monitor change notifications are not supported on this platform".to_string())
+    }
+}
+
+/// Lists the active monitors, falling back to what the window system
+/// reports through `window` when no native backend applies.
+pub fn current(window: &tauri::Window) -> Result<Vec<Monitor>, String> {
+    if let Some(result) = enumerate() {
+        let mut layout = result?;
+        apply_scale_factors(&mut layout, window);
+        return Ok(layout);
+    }
+
+    let available = window.available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    let primary = window.primary_monitor()
+        .map_err(|e| format!("Failed to get primary monitor: {}", e))?;
+    Ok(from_tauri(available, primary))
+}
+
+/// Copies the scale factors the window system uses for each monitor.
+///
+/// Native backends only know the geometry; the scale that matters is the
+/// one the webview renders with, so it is taken from Tauri. Monitors that
+/// can't be matched by position keep a factor of 1.
+pub fn apply_scale_factors(layout: &mut [Monitor], window: &tauri::Window) {
+    let scales: Vec<((i32, i32), f64)> = window.available_monitors().unwrap_or_default()
+        .iter()
+        .map(|monitor| ((monitor.position().x, monitor.position().y), monitor.scale_factor()))
+        .collect();
+    match_scale_factors(layout, &scales);
+}
+
+/// Gives each monitor the scale factor listed for its position in `scales`.
+fn match_scale_factors(layout: &mut [Monitor], scales: &[((i32, i32), f64)]) {
+    for monitor in layout.iter_mut() {
+        monitor.scale_factor = scales.iter()
+            .find(|(position, _)| *position == (monitor.x, monitor.y))
+            .map(|(_, scale_factor)| *scale_factor)
+            .unwrap_or(1.0);
+    }
+}
+
+/// Converts the monitor list reported by the window system.
+///
+/// Tauri doesn't expose rotation, so every monitor reports 0.
+pub fn from_tauri(monitors: Vec<tauri::Monitor>, primary: Option<tauri::Monitor>) -> Vec<Monitor> {
+    let primary_position = primary.map(|monitor| *monitor.position());
+
+    monitors.into_iter()
+        .enumerate()
+        .map(|(index, monitor)| Monitor {
+            id: monitor.name().cloned().unwrap_or_else(|| format!("monitor-{}", index)),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+            rotation: 0,
+            primary: primary_position == Some(*monitor.position()),
+            scale_factor: monitor.scale_factor(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(id: &str, x: i32, y: i32) -> Monitor {
+        Monitor {
+            id: id.to_string(),
+            x,
+            y,
+            width: 1920,
+            height: 1080,
+            rotation: 0,
+            primary: false,
+            scale_factor: 3.0,
+        }
+    }
+
+    #[test]
+    fn scale_factors_are_matched_by_position() {
+        let mut layout = vec![monitor("eDP-1", 0, 0), monitor("DP-1", 3840, 0), monitor("DP-2", -1920, 200)];
+        match_scale_factors(&mut layout, &[((3840, 0), 1.0), ((0, 0), 2.0), ((-1920, 0), 1.5)]);
+
+        let scales: Vec<f64> = layout.iter().map(|monitor| monitor.scale_factor).collect();
+        assert_eq!(scales, [2.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn scale_factors_default_to_one() {
+        let mut layout = vec![monitor("DP-1", 0, 0)];
+        match_scale_factors(&mut layout, &[]);
+        assert_eq!(layout[0].scale_factor, 1.0);
+    }
+}
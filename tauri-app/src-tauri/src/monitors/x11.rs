@@ -0,0 +1,163 @@
+use std::thread;
+use std::time::Duration;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::{self, ConnectionExt as _};
+use x11rb::protocol::xproto::Window;
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+use super::Monitor;
+
+/// XRandR view of the monitors attached to an X screen.
+pub struct X11Monitors {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl X11Monitors {
+    /// Connects to `display`, or to `$DISPLAY` when `None`, and checks that
+    /// the server speaks RandR 1.3 or newer.
+    pub fn connect(display: Option<&str>) -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(display)
+            .map_err(|e| format!("Failed to connect to X server: {}", e))?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let version = conn.randr_query_version(1, 3)
+            .map_err(|e| format!("Failed to send RRQueryVersion: {}", e))?
+            .reply()
+            .map_err(|e| format!("XRandR is not available: {}", e))?;
+
+        if (version.major_version, version.minor_version) < (1, 3) {
+            return Err(format!(
+                "XRandR {}.{} is too old, 1.3 is required",
+                version.major_version, version.minor_version
+            ));
+        }
+
+        Ok(Self { conn, root })
+    }
+
+    /// Every connected output that is currently driven by a CRTC.
+    pub fn list(&self) -> Result<Vec<Monitor>, String> {
+        let resources = self.conn.randr_get_screen_resources_current(self.root)
+            .map_err(|e| format!("Failed to send RRGetScreenResourcesCurrent: {}", e))?
+            .reply()
+            .map_err(|e| format!("RRGetScreenResourcesCurrent failed: {}", e))?;
+
+        let primary = self.conn.randr_get_output_primary(self.root)
+            .map_err(|e| format!("Failed to send RRGetOutputPrimary: {}", e))?
+            .reply()
+            .map_err(|e| format!("RRGetOutputPrimary failed: {}", e))?
+            .output;
+
+        let mut monitors = Vec::new();
+        for &output in &resources.outputs {
+            let info = self.conn.randr_get_output_info(output, resources.config_timestamp)
+                .map_err(|e| format!("Failed to send RRGetOutputInfo: {}", e))?
+                .reply()
+                .map_err(|e| format!("RRGetOutputInfo failed: {}", e))?;
+
+            // Disconnected or disabled outputs have no CRTC
+            if info.connection != randr::Connection::CONNECTED || info.crtc == 0 {
+                continue;
+            }
+
+            let crtc = self.conn.randr_get_crtc_info(info.crtc, resources.config_timestamp)
+                .map_err(|e| format!("Failed to send RRGetCrtcInfo: {}", e))?
+                .reply()
+                .map_err(|e| format!("RRGetCrtcInfo failed: {}", e))?;
+
+            monitors.push(Monitor {
+                id: String::from_utf8_lossy(&info.name).into_owned(),
+                x: crtc.x as i32,
+                y: crtc.y as i32,
+                width: crtc.width as u32,
+                height: crtc.height as u32,
+                rotation: rotation_degrees(crtc.rotation),
+                primary: output == primary,
+                scale_factor: 1.0,
+            });
+        }
+
+        // Without an explicit primary, the output at the origin acts as one
+        if !monitors.iter().any(|monitor| monitor.primary) {
+            if let Some(monitor) = monitors.iter_mut().find(|monitor| monitor.x == 0 && monitor.y == 0) {
+                monitor.primary = true;
+            }
+        }
+
+        Ok(monitors)
+    }
+}
+
+impl X11Monitors {
+    /// Subscribes to RandR notifications for outputs, CRTCs and the screen.
+    pub fn select_changes(&self) -> Result<(), String> {
+        let mask = randr::NotifyMask::SCREEN_CHANGE
+            | randr::NotifyMask::CRTC_CHANGE
+            | randr::NotifyMask::OUTPUT_CHANGE;
+
+        self.conn.randr_select_input(self.root, mask)
+            .map_err(|e| format!("Failed to send RRSelectInput: {}", e))?
+            .check()
+            .map_err(|e| format!("RRSelectInput failed: {}", e))?;
+        Ok(())
+    }
+
+    /// Blocks until the monitor configuration changes.
+    ///
+    /// A single hotplug or mode switch produces a burst of notifications;
+    /// they are collapsed into one return. Requires a prior call to
+    /// `select_changes`.
+    pub fn wait_for_change(&self) -> Result<(), String> {
+        loop {
+            let event = self.conn.wait_for_event()
+                .map_err(|e| format!("Lost connection to X server: {}", e))?;
+
+            if let Event::RandrScreenChangeNotify(_) | Event::RandrNotify(_) = event {
+                break;
+            }
+        }
+
+        thread::sleep(Duration::from_millis(200));
+        while self.conn.poll_for_event()
+            .map_err(|e| format!("Lost connection to X server: {}", e))?
+            .is_some()
+        {}
+
+        Ok(())
+    }
+}
+
+fn rotation_degrees(rotation: randr::Rotation) -> u16 {
+    if rotation.contains(randr::Rotation::ROTATE90) {
+        90
+    } else if rotation.contains(randr::Rotation::ROTATE180) {
+        180
+    } else if rotation.contains(randr::Rotation::ROTATE270) {
+        270
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use randr::Rotation;
+
+    #[test]
+    fn rotation_is_reported_in_clockwise_degrees() {
+        assert_eq!(rotation_degrees(Rotation::ROTATE0), 0);
+        assert_eq!(rotation_degrees(Rotation::ROTATE90), 90);
+        assert_eq!(rotation_degrees(Rotation::ROTATE180), 180);
+        assert_eq!(rotation_degrees(Rotation::ROTATE270), 270);
+    }
+
+    #[test]
+    fn reflection_does_not_change_the_rotation() {
+        assert_eq!(rotation_degrees(Rotation::ROTATE0 | Rotation::REFLECT_X), 0);
+        assert_eq!(rotation_degrees(Rotation::ROTATE90 | Rotation::REFLECT_Y), 90);
+        assert_eq!(rotation_degrees(Rotation::ROTATE270 | Rotation::REFLECT_X | Rotation::REFLECT_Y), 270);
+    }
+}
@@ -0,0 +1,302 @@
+// One transparent overlay window per monitor
+//
+// The `main` window stays on the monitor it was opened on and hosts the
+// panels; every other monitor gets a click-through `overlay-<output>` window
+// that only renders the fluid.
+
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Window, WindowBuilder, WindowUrl};
+
+use crate::events::OverlayPoint;
+use crate::monitors::Monitor;
+
+pub const MAIN_LABEL: &str = "main";
+const LABEL_PREFIX: &str = "overlay-";
+
+/// A window and the monitor it covers.
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    pub label: String,
+    pub monitor: Monitor,
+    /// Gets no cursor input, e.g. under a full-screen application
+    pub paused: bool,
+}
+
+impl Overlay {
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.monitor.x
+            && y >= self.monitor.y
+            && x < self.monitor.x + self.monitor.width as i32
+            && y < self.monitor.y + self.monitor.height as i32
+    }
+}
+
+/// Window that should receive a pointer event, with the position translated
+/// into the window's own coordinate space.
+pub struct OverlayTarget {
+    pub window: Window,
+    pub point: OverlayPoint,
+    pub width: i32,
+    pub height: i32,
+}
+
+/// Finds the overlay under the global position `(x, y)`.
+///
+/// Before the first `sync`, everything goes to the main window in global
+/// coordinates over `screen`.
+pub fn target_at(
+    app_handle: &AppHandle,
+    overlays: &Mutex<Vec<Overlay>>,
+    x: i32,
+    y: i32,
+    screen: (i32, i32),
+) -> Option<OverlayTarget> {
+    let overlays = overlays.lock().ok()?;
+
+    if overlays.is_empty() {
+        let window = app_handle.get_window(MAIN_LABEL)?;
+        let scale_factor = window.scale_factor().unwrap_or(1.0);
+        return Some(OverlayTarget {
+            window,
+            point: OverlayPoint::new(x, y, scale_factor),
+            width: screen.0,
+            height: screen.1,
+        });
+    }
+
+    let (overlay, local_x, local_y) = locate(&overlays, x, y)?;
+    if overlay.paused {
+        return None;
+    }
+    Some(OverlayTarget {
+        window: app_handle.get_window(&overlay.label)?,
+        point: OverlayPoint::new(local_x, local_y, overlay.monitor.scale_factor),
+        width: overlay.monitor.width as i32,
+        height: overlay.monitor.height as i32,
+    })
+}
+
+/// The overlay covering the global position `(x, y)`, with the position
+/// relative to its top-left corner. `None` in gaps between monitors.
+fn locate(overlays: &[Overlay], x: i32, y: i32) -> Option<(&Overlay, i32, i32)> {
+    let overlay = overlays.iter().find(|overlay| overlay.contains(x, y))?;
+    Some((overlay, x - overlay.monitor.x, y - overlay.monitor.y))
+}
+
+/// Label of the overlay on the monitor around `point`, e.g. the middle of a
+/// window. Without a point, or before the first `sync`, that is the main
+/// window; `None` when no overlay covers the point.
+pub fn covering(overlays: &[Overlay], point: Option<(i32, i32)>) -> Option<&str> {
+    match point {
+        Some((x, y)) if !overlays.is_empty() => locate(overlays, x, y).map(|(overlay, _, _)| overlay.label.as_str()),
+        _ => Some(MAIN_LABEL),
+    }
+}
+
+/// Returns true for windows created by `sync`.
+pub fn is_secondary(label: &str) -> bool {
+    label.starts_with(LABEL_PREFIX)
+}
+
+/// Creates, moves and closes overlay windows so that every monitor in
+/// `layout` is covered by exactly one of them.
+///
+/// Must not be called from the main thread: creating a window waits for
+/// the event loop.
+pub fn sync(app_handle: &AppHandle, overlays: &Mutex<Vec<Overlay>>, layout: &[Monitor]) -> Result<(), String> {
+    let main_window = app_handle.get_window(MAIN_LABEL)
+        .ok_or_else(|| "Main window not found".to_string())?;
+    let main_position = main_window.current_monitor().ok().flatten()
+        .map(|monitor| (monitor.position().x, monitor.position().y));
+    let main_monitor = main_monitor(main_position, layout)
+        .ok_or_else(|| "No monitors to place overlays on".to_string())?;
+
+    // The main window's own monitor may not be covered, e.g. with `--monitor`
+    if main_position != Some((main_monitor.x, main_monitor.y)) {
+        println!("Moving main window to {}", main_monitor.id);
+        place_window(&main_window, main_monitor)?;
+    }
+
+    let mut next = vec![Overlay {
+        label: MAIN_LABEL.to_string(),
+        monitor: main_monitor.clone(),
+        paused: false,
+    }];
+    for monitor in layout.iter().filter(|monitor| monitor.id != main_monitor.id) {
+        next.push(Overlay {
+            label: overlay_label(&monitor.id),
+            monitor: monitor.clone(),
+            paused: false,
+        });
+    }
+
+    // Close overlays whose monitor went away
+    for (label, window) in app_handle.windows() {
+        if is_secondary(&label) && !next.iter().any(|overlay| overlay.label == label) {
+            println!("Closing overlay {}", label);
+            let _ = window.close();
+        }
+    }
+
+    for overlay in next.iter().filter(|overlay| is_secondary(&overlay.label)) {
+        let window = match app_handle.get_window(&overlay.label) {
+            Some(window) => window,
+            None => {
+                println!("Opening overlay {} on {}", overlay.label, overlay.monitor.id);
+                create_window(app_handle, &overlay.label, main_window.is_visible().unwrap_or(true))?
+            }
+        };
+        place_window(&window, &overlay.monitor)?;
+    }
+
+    let mut overlays = overlays.lock()
+        .map_err(|e| format!("Failed to lock overlays: {}", e))?;
+    // Windows that stay keep their pause
+    for overlay in next.iter_mut() {
+        overlay.paused = overlays.iter().any(|current| current.label == overlay.label && current.paused);
+    }
+    *overlays = next;
+    Ok(())
+}
+
+/// The monitor for the main window: the one at `position`, where it is now,
+/// else the primary one, else the first.
+fn main_monitor(position: Option<(i32, i32)>, layout: &[Monitor]) -> Option<&Monitor> {
+    layout.iter()
+        .find(|monitor| position == Some((monitor.x, monitor.y)))
+        .or_else(|| layout.iter().find(|monitor| monitor.primary))
+        .or_else(|| layout.first())
+}
+
+fn overlay_label(monitor_id: &str) -> String {
+    // Window labels may only contain alphanumerics and `-/:_`
+    let id: String = monitor_id.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    format!("{}{}", LABEL_PREFIX, id)
+}
+
+/// Opens a secondary overlay, hidden while the others are (e.g. `--start-hidden`).
+fn create_window(app_handle: &AppHandle, label: &str, visible: bool) -> Result<Window, String> {
+    let window = WindowBuilder::new(app_handle, label, WindowUrl::App("index.html".into()))
+        .title("Magic Cursor")
+        .transparent(true)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .visible(visible)
+        .build()
+        .map_err(|e| format!("Failed to create overlay window {}: {}", label, e))?;
+
+    // Secondary overlays never host panels, so they are always click-through
+    window.set_ignore_cursor_events(true)
+        .map_err(|e| format!("Failed to set ignore cursor events: {}", e))?;
+    Ok(window)
+}
+
+fn place_window(window: &Window, monitor: &Monitor) -> Result<(), String> {
+    window.set_position(PhysicalPosition::new(monitor.x, monitor.y))
+        .map_err(|e| format!("Failed to set window position: {}", e))?;
+    window.set_size(PhysicalSize::new(monitor.width, monitor.height))
+        .map_err(|e| format!("Failed to set window size: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(id: &str, x: i32, y: i32, width: u32, height: u32) -> Monitor {
+        Monitor {
+            id: id.to_string(),
+            x,
+            y,
+            width,
+            height,
+            rotation: 0,
+            primary: false,
+            scale_factor: 1.0,
+        }
+    }
+
+    /// A 1920x1080 monitor with a 2560x1440 one to its left, ending 40px
+    /// short of it, and a portrait one below.
+    fn layout() -> Vec<Overlay> {
+        vec![
+            Overlay { label: MAIN_LABEL.to_string(), monitor: monitor("DP-1", 0, 0, 1920, 1080), paused: false },
+            Overlay { label: overlay_label("DP-2"), monitor: monitor("DP-2", -2600, -200, 2560, 1440), paused: false },
+            Overlay { label: overlay_label("HDMI-1"), monitor: monitor("HDMI-1", 0, 1080, 1080, 1920), paused: false },
+        ]
+    }
+
+    #[test]
+    fn contains_includes_the_top_left_edge_only() {
+        let overlay = &layout()[0];
+        assert!(overlay.contains(0, 0));
+        assert!(overlay.contains(1919, 1079));
+        assert!(!overlay.contains(1920, 0));
+        assert!(!overlay.contains(0, 1080));
+        assert!(!overlay.contains(-1, 500));
+    }
+
+    #[test]
+    fn locate_translates_into_the_overlay() {
+        let overlays = layout();
+        let label = |x, y| locate(&overlays, x, y).map(|(overlay, x, y)| (overlay.label.as_str(), x, y));
+
+        assert_eq!(label(100, 200), Some((MAIN_LABEL, 100, 200)));
+        assert_eq!(label(-2600, -200), Some(("overlay-DP-2", 0, 0)));
+        assert_eq!(label(-41, 1239), Some(("overlay-DP-2", 2559, 1439)));
+        assert_eq!(label(500, 1080), Some(("overlay-HDMI-1", 500, 0)));
+    }
+
+    #[test]
+    fn locate_finds_nothing_between_or_past_monitors() {
+        let overlays = layout();
+        assert!(locate(&overlays, -40, 0).is_none());
+        assert!(locate(&overlays, -1, 100).is_none());
+        assert!(locate(&overlays, 1500, 1500).is_none());
+        assert!(locate(&overlays, 0, 3000).is_none());
+        assert!(locate(&[], 0, 0).is_none());
+    }
+
+    #[test]
+    fn covering_names_the_overlay_under_the_point() {
+        let overlays = layout();
+        assert_eq!(covering(&overlays, Some((960, 540))), Some(MAIN_LABEL));
+        assert_eq!(covering(&overlays, Some((-1320, 520))), Some("overlay-DP-2"));
+        assert_eq!(covering(&overlays, Some((540, 2040))), Some("overlay-HDMI-1"));
+        assert_eq!(covering(&overlays, Some((-20, 0))), None);
+    }
+
+    #[test]
+    fn covering_falls_back_to_the_main_window() {
+        assert_eq!(covering(&layout(), None), Some(MAIN_LABEL));
+        assert_eq!(covering(&[], Some((5000, 5000))), Some(MAIN_LABEL));
+    }
+
+    #[test]
+    fn main_monitor_prefers_where_the_window_is() {
+        let mut monitors: Vec<Monitor> = layout().into_iter().map(|overlay| overlay.monitor).collect();
+        monitors[2].primary = true;
+
+        assert_eq!(main_monitor(Some((-2600, -200)), &monitors).map(|m| m.id.as_str()), Some("DP-2"));
+        assert_eq!(main_monitor(Some((5, 5)), &monitors).map(|m| m.id.as_str()), Some("HDMI-1"));
+        assert_eq!(main_monitor(None, &monitors).map(|m| m.id.as_str()), Some("HDMI-1"));
+
+        monitors[2].primary = false;
+        assert_eq!(main_monitor(None, &monitors).map(|m| m.id.as_str()), Some("DP-1"));
+        assert!(main_monitor(None, &[]).is_none());
+    }
+
+    #[test]
+    fn overlay_labels_only_use_allowed_characters() {
+        assert_eq!(overlay_label("DP-1"), "overlay-DP-1");
+        assert_eq!(overlay_label("HDMI-A-1"), "overlay-HDMI-A-1");
+        assert_eq!(overlay_label("monitor 2.1"), "overlay-monitor_2_1");
+        assert!(is_secondary(&overlay_label("DP-1")));
+        assert!(!is_secondary(MAIN_LABEL));
+    }
+}
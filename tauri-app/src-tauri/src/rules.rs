@@ -0,0 +1,284 @@
+// Applies the per-application rules as focus moves
+//
+// The engine only decides what changes; the caller carries out the returned
+// effects. That keeps it free of Tauri, so a scripted `FocusSource` can
+// drive it. A rule's effect is undone when its application loses focus,
+// unless the user changed the same setting in the meantime. Profiles a rule
+// switches to are only used, never saved as the user's choice.
+
+use crate::config::rules::RuleSet;
+use crate::config::{AppRule, RuleAction};
+use crate::focus::FocusedWindow;
+
+/// A change for the caller to make.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    SetPaused(bool),
+    /// Use the profile until `RestoreProfile`
+    SwitchProfile(String),
+    /// Go back to the user's own profile
+    RestoreProfile,
+    /// 1 is the profile's own intensity
+    SetIntensity(f64),
+}
+
+/// How to take back what a rule did.
+enum Undo {
+    Resume,
+    /// Restore the user's profile, if `to` is still active
+    SwitchBack { to: String },
+    RestoreIntensity,
+}
+
+#[derive(Default)]
+pub struct RuleEngine {
+    rules: RuleSet,
+    focused: Option<FocusedWindow>,
+    /// The rule in effect and how to undo it
+    applied: Option<(AppRule, Option<Undo>)>,
+}
+
+impl RuleEngine {
+    /// Records that `focused` has focus now and returns the effects of the
+    /// move, like `update`.
+    pub fn focus_changed(
+        &mut self,
+        focused: Option<FocusedWindow>,
+        rules: &[AppRule],
+        active_profile: &str,
+        paused: bool,
+    ) -> Vec<Effect> {
+        // Rules keep applying while the user works in the overlay's panels
+        if focused.as_ref().is_some_and(FocusedWindow::is_ours) {
+            return Vec::new();
+        }
+        self.focused = focused;
+        self.update(rules, active_profile, paused)
+    }
+
+    /// Matches the focused window against `rules` and returns what to change:
+    /// undoing the previous rule, then applying the new one. `active_profile`
+    /// and `paused` are the current state.
+    pub fn update(&mut self, rules: &[AppRule], active_profile: &str, paused: bool) -> Vec<Effect> {
+        if !self.rules.is_for(rules) {
+            self.rules = RuleSet::new(rules);
+        }
+        let rule = self.focused.as_ref().and_then(|window| self.rules.find(window)).cloned();
+        if rule.as_ref() == self.applied.as_ref().map(|(applied, _)| applied) {
+            return Vec::new();
+        }
+
+        let mut effects = Vec::new();
+        // The user's profile is only known to the caller once restored
+        let mut restored_profile = false;
+        let mut paused = paused;
+        match self.applied.take().and_then(|(_, undo)| undo) {
+            Some(Undo::Resume) if paused => {
+                effects.push(Effect::SetPaused(false));
+                paused = false;
+            }
+            Some(Undo::SwitchBack { to }) if to == active_profile => {
+                effects.push(Effect::RestoreProfile);
+                restored_profile = true;
+            }
+            Some(Undo::RestoreIntensity) => effects.push(Effect::SetIntensity(1.0)),
+            _ => {}
+        }
+
+        if let Some(rule) = rule {
+            // Nothing to undo for what was already the case
+            let undo = match &rule.action {
+                RuleAction::Pause if !paused => {
+                    effects.push(Effect::SetPaused(true));
+                    Some(Undo::Resume)
+                }
+                RuleAction::SwitchProfile { profile } if restored_profile || profile != active_profile => {
+                    effects.push(Effect::SwitchProfile(profile.clone()));
+                    Some(Undo::SwitchBack { to: profile.clone() })
+                }
+                RuleAction::LowerIntensity { intensity } => {
+                    effects.push(Effect::SetIntensity(*intensity));
+                    Some(Undo::RestoreIntensity)
+                }
+                _ => None,
+            };
+            self.applied = Some((rule, undo));
+        }
+        effects
+    }
+
+    /// Keeps a pause the user chose from being undone with the rule.
+    pub fn forget_pause(&mut self) {
+        if let Some((_, undo)) = &mut self.applied {
+            if matches!(undo, Some(Undo::Resume)) {
+                *undo = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::focus::{self, tests::{window, ScriptedFocus}};
+
+    /// Carries out effects the way the app does.
+    struct App {
+        engine: RuleEngine,
+        rules: Vec<AppRule>,
+        profile: String,
+        /// The user's own choice, which `profile` goes back to
+        saved_profile: String,
+        paused: bool,
+        intensity: f64,
+        effects: Vec<Vec<Effect>>,
+    }
+
+    impl App {
+        fn new(rules: Vec<AppRule>) -> Self {
+            Self {
+                engine: RuleEngine::default(),
+                rules,
+                profile: "Default".to_string(),
+                saved_profile: "Default".to_string(),
+                paused: false,
+                intensity: 1.0,
+                effects: Vec::new(),
+            }
+        }
+
+        fn focus(&mut self, focused: Option<FocusedWindow>) {
+            let effects = self.engine.focus_changed(focused, &self.rules, &self.profile, self.paused);
+            for effect in &effects {
+                match effect {
+                    Effect::SetPaused(paused) => self.paused = *paused,
+                    Effect::SwitchProfile(profile) => self.profile = profile.clone(),
+                    Effect::RestoreProfile => self.profile = self.saved_profile.clone(),
+                    Effect::SetIntensity(intensity) => self.intensity = *intensity,
+                }
+            }
+            self.effects.push(effects);
+        }
+
+        /// Follows `script` through `focus::watch` until it ends.
+        fn run(&mut self, script: Vec<Option<FocusedWindow>>) {
+            let _ = focus::watch(ScriptedFocus::boxed(script), |focused| self.focus(focused));
+        }
+    }
+
+    fn rule(wm_class: &str, action: RuleAction) -> AppRule {
+        AppRule {
+            wm_class: Some(wm_class.to_string()),
+            action,
+            ..AppRule::default()
+        }
+    }
+
+    fn switch_to(profile: &str) -> RuleAction {
+        RuleAction::SwitchProfile { profile: profile.to_string() }
+    }
+
+    #[test]
+    fn undoes_each_action_when_focus_leaves() {
+        let mut app = App::new(vec![
+            rule("mpv", RuleAction::Pause),
+            rule("code", switch_to("Calm")),
+            rule("gimp", RuleAction::LowerIntensity { intensity: 0.25 }),
+        ]);
+        app.run(vec![
+            window("mpv", "video.mkv"),
+            None,
+            window("code", "main.rs"),
+            window("Firefox", "News"),
+            window("gimp", "Untitled"),
+            None,
+        ]);
+
+        assert_eq!(app.effects, vec![
+            vec![Effect::SetPaused(true)],
+            vec![Effect::SetPaused(false)],
+            vec![Effect::SwitchProfile("Calm".to_string())],
+            vec![Effect::RestoreProfile],
+            vec![Effect::SetIntensity(0.25)],
+            vec![Effect::SetIntensity(1.0)],
+        ]);
+        assert!(!app.paused);
+        assert_eq!(app.profile, "Default");
+        assert_eq!(app.intensity, 1.0);
+    }
+
+    #[test]
+    fn undoes_the_previous_rule_before_applying_the_next() {
+        let mut app = App::new(vec![rule("mpv", RuleAction::Pause), rule("code", switch_to("Calm"))]);
+        app.run(vec![window("mpv", "video.mkv"), window("code", "main.rs")]);
+
+        assert_eq!(app.effects[1], vec![
+            Effect::SetPaused(false),
+            Effect::SwitchProfile("Calm".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn switches_again_after_restoring_the_profile() {
+        let mut app = App::new(vec![rule("code", switch_to("Calm")), rule("gimp", switch_to("Calm"))]);
+        app.run(vec![window("code", "main.rs"), window("gimp", "Untitled"), None]);
+
+        assert_eq!(app.effects[1], vec![Effect::RestoreProfile, Effect::SwitchProfile("Calm".to_string())]);
+        assert_eq!(app.effects[2], vec![Effect::RestoreProfile]);
+        assert_eq!(app.profile, "Default");
+    }
+
+    #[test]
+    fn leaves_what_was_already_the_case() {
+        let mut app = App::new(vec![rule("mpv", RuleAction::Pause), rule("code", switch_to("Default"))]);
+        app.paused = true;
+        app.run(vec![window("mpv", "video.mkv"), None, window("code", "main.rs"), None]);
+
+        assert!(app.effects.iter().all(Vec::is_empty));
+        assert!(app.paused);
+    }
+
+    #[test]
+    fn keeps_a_profile_the_user_switched_to() {
+        let mut app = App::new(vec![rule("code", switch_to("Calm"))]);
+        app.focus(window("code", "main.rs"));
+        app.profile = "Vivid".to_string();
+        app.saved_profile = "Vivid".to_string();
+        app.focus(None);
+
+        assert_eq!(app.effects[1], Vec::new());
+        assert_eq!(app.profile, "Vivid");
+    }
+
+    #[test]
+    fn keeps_a_pause_the_user_chose() {
+        let mut app = App::new(vec![rule("mpv", RuleAction::Pause)]);
+        app.focus(window("mpv", "video.mkv"));
+        app.engine.forget_pause();
+        app.focus(None);
+
+        assert_eq!(app.effects[1], Vec::new());
+        assert!(app.paused);
+    }
+
+    #[test]
+    fn ignores_focus_on_our_own_windows() {
+        let mut app = App::new(vec![rule("mpv", RuleAction::Pause)]);
+        let mut ours = window("magic-cursor", "Settings").unwrap();
+        ours.pid = Some(std::process::id());
+        app.run(vec![window("mpv", "video.mkv"), Some(ours), window("mpv", "video.mkv")]);
+
+        assert_eq!(app.effects, vec![vec![Effect::SetPaused(true)], Vec::new(), Vec::new()]);
+        assert!(app.paused);
+    }
+
+    #[test]
+    fn rematches_when_the_rules_change() {
+        let mut app = App::new(vec![rule("mpv", RuleAction::Pause)]);
+        app.focus(window("code", "main.rs"));
+        app.rules.push(rule("code", RuleAction::LowerIntensity { intensity: 0.5 }));
+        let effects = app.engine.update(&app.rules, &app.profile, app.paused);
+
+        assert_eq!(effects, vec![Effect::SetIntensity(0.5)]);
+    }
+}
@@ -0,0 +1,54 @@
+// Unix sockets for talking to the running app
+//
+// They live in `$XDG_RUNTIME_DIR`, which only the user can enter. Without
+// one they go to a private directory under /tmp instead. Either way every
+// connection is also checked against our uid, so other users on the
+// machine can never drive the overlay.
+
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+
+use rustix::net::sockopt::socket_peercred;
+use rustix::process::getuid;
+
+/// Path of the socket called `name`.
+pub fn runtime_path(name: &str) -> Result<PathBuf, String> {
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir).join(name));
+    }
+
+    let uid = getuid().as_raw();
+    let dir = env::temp_dir().join(format!("magic-cursor-{}", uid));
+    if let Err(e) = DirBuilder::new().mode(0o700).create(&dir) {
+        if e.kind() != std::io::ErrorKind::AlreadyExists {
+            return Err(format!("Failed to create {}: {}", dir.display(), e));
+        }
+    }
+
+    // Someone else may have created it first
+    let metadata = fs::symlink_metadata(&dir)
+        .map_err(|e| format!("Failed to inspect {}: {}", dir.display(), e))?;
+    if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+        return Err(format!("{} is not a private directory of this user", dir.display()));
+    }
+    Ok(dir.join(name))
+}
+
+/// Makes a freshly bound socket usable by its owner only.
+pub fn restrict(path: &Path) -> Result<(), String> {
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to restrict {}: {}", path.display(), e))
+}
+
+/// Rejects connections from processes of other users.
+pub fn check_peer(stream: &UnixStream) -> Result<(), String> {
+    let peer = socket_peercred(stream)
+        .map_err(|e| format!("Failed to identify peer: {}", e))?;
+    if peer.uid != getuid() {
+        return Err(format!("Refused connection from uid {}", peer.uid.as_raw()));
+    }
+    Ok(())
+}
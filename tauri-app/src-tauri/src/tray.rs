@@ -0,0 +1,55 @@
+// System tray menu
+//
+// The menu lists the profiles, so it is rebuilt from the config whenever
+// that changes.
+
+use tauri::{AppHandle, CustomMenuItem, SystemTrayMenu, SystemTrayMenuItem, SystemTraySubmenu};
+
+use crate::config::Config;
+
+const PROFILE_ITEM_PREFIX: &str = "profile:";
+
+pub fn build_menu(config: &Config, click_through: bool) -> SystemTrayMenu {
+    let quit = CustomMenuItem::new("quit".to_string(), "Quit");
+    let hide = CustomMenuItem::new("hide".to_string(), "Hide");
+    let show = CustomMenuItem::new("show".to_string(), "Show");
+    let toggle_click_through_item = CustomMenuItem::new(
+        "toggle_click_through".to_string(),
+        if click_through { "Disable Click-Through" } else { "Enable Click-Through" },
+    );
+    let color_presets = CustomMenuItem::new("color_presets".to_string(), "Color Presets");
+    let theme_customizer = CustomMenuItem::new("theme_customizer".to_string(), "Theme Customizer");
+    let settings = CustomMenuItem::new("settings".to_string(), "Settings");
+    let welcome = CustomMenuItem::new("welcome".to_string(), "Show Welcome");
+
+    let mut profiles = SystemTrayMenu::new();
+    for profile in &config.profiles {
+        let item = CustomMenuItem::new(format!("{}{}", PROFILE_ITEM_PREFIX, profile.name), &profile.name);
+        profiles = profiles.add_item(if profile.name == config.active_profile { item.selected() } else { item });
+    }
+
+    SystemTrayMenu::new()
+        .add_item(show)
+        .add_item(hide)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(toggle_click_through_item)
+        .add_submenu(SystemTraySubmenu::new("Profiles", profiles))
+        .add_item(color_presets)
+        .add_item(theme_customizer)
+        .add_item(settings)
+        .add_item(welcome)
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(quit)
+}
+
+/// Replaces the tray menu after the config changed.
+pub fn refresh(app_handle: &AppHandle, config: &Config, click_through: bool) {
+    if let Err(e) = app_handle.tray_handle().set_menu(build_menu(config, click_through)) {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
+}
+
+/// Name of the profile a tray item switches to, if it is a profile item.
+pub fn profile_for_item(id: &str) -> Option<&str> {
+    id.strip_prefix(PROFILE_ITEM_PREFIX)
+}
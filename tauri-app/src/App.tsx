@@ -1,13 +1,32 @@
-import { useEffect, useState, useCallback } from 'react';
-import { listen } from '@tauri-apps/api/event';
+import { useEffect, useState, useCallback, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import { platform } from '@tauri-apps/api/os';
+import { appWindow } from '@tauri-apps/api/window';
 import MagicMouse from './components/MagicMouse';
 import Settings, { FluidConfig } from './components/Settings';
 import ClickThroughIndicator from './components/ClickThroughIndicator';
+import ConfigWarning from './components/ConfigWarning';
 import Welcome from './components/Welcome';
 import AndroidHome from './components/AndroidHome';
 import ThemeCustomizer from './components/ThemeCustomizer';
+import { listenBackend } from './events';
+import { useInteractionLease } from './interaction';
+import {
+  Config,
+  ConfigError,
+  DEFAULT_CONFIG,
+  activeProfile,
+  clearLegacySettings,
+  isConfigNew,
+  loadConfig,
+  Profile,
+  saveConfig,
+  withActiveProfile,
+  withLegacySettings,
+} from './config';
+
+// Windows the backend opens on additional monitors only render the fluid
+const isSecondaryOverlay = appWindow.label.startsWith('overlay-');
 
 function App(): JSX.Element {
   const [isAndroid, setIsAndroid] = useState(false);
@@ -15,102 +34,95 @@ function App(): JSX.Element {
   const [showWelcome, setShowWelcome] = useState(false);
   const [isSettingsOpen, setIsSettingsOpen] = useState(false);
   const [isThemeCustomizerOpen, setIsThemeCustomizerOpen] = useState(false);
-  const [wasClickThroughEnabled, setWasClickThroughEnabled] = useState(true);
-  const [colorHueRange, setColorHueRange] = useState<[number, number]>([0, 1]);
-  const [colorSaturation, setColorSaturation] = useState(1.0);
-  const [colorBrightness, setColorBrightness] = useState(1.0);
-  const [config, setConfig] = useState<FluidConfig>({
-    PRESSURE: 1,
-    CURL: 4,
-    SPLAT_RADIUS: 0.2,
-    SPLAT_FORCE: 6000,
-    DENSITY_DISSIPATION: 3.5,
-    VELOCITY_DISSIPATION: 2,
-    COLOR_UPDATE_SPEED: 10,
-    SHADING: false,
-  });
+  const [appConfig, setAppConfig] = useState<Config>(DEFAULT_CONFIG);
+  const appConfigRef = useRef<Config>(DEFAULT_CONFIG);
+  // Lowered by a per-application rule while its application has focus
+  const [intensity, setIntensity] = useState(1);
+  const { fluid: config, palette, behavior } = activeProfile(appConfig);
+  const {
+    hueRange: colorHueRange,
+    saturation: colorSaturation,
+    brightness: colorBrightness,
+  } = palette;
+
+  const applyConfig = (next: Config): void => {
+    appConfigRef.current = next;
+    setAppConfig(next);
+  };
+
+  // Persisted by the backend, which echoes it to every window via config-changed
+  const saveChanged = (next: Config): void => {
+    applyConfig(next);
+    saveConfig(next).catch((e: ConfigError) => console.error('Failed to save config:', e.message));
+  };
+
+  const updateConfig = (changes: Partial<Config>): void => {
+    saveChanged({ ...appConfigRef.current, ...changes });
+  };
+
+  const updateActiveProfile = (changes: Partial<Profile>): void => {
+    saveChanged(withActiveProfile(appConfigRef.current, changes));
+  };
+
+  // Panels need the mouse; the backend restores click-through once all close
+  useInteractionLease(isSettingsOpen);
+  useInteractionLease(isThemeCustomizerOpen);
+  useInteractionLease(showWelcome);
 
   useEffect(() => {
     let unlistenSettings: (() => void) | null = null;
     let unlistenThemeCustomizer: (() => void) | null = null;
     let unlistenColorPresets: (() => void) | null = null;
     let unlistenWelcome: (() => void) | null = null;
+    let unlistenConfig: (() => void) | null = null;
+    let unlistenIntensity: (() => void) | null = null;
     let isMounted = true;
 
     const setupListeners = async () => {
       try {
-        unlistenSettings = await listen('open-settings', async () => {
-          if (!isMounted) return;
-          try {
-            const currentState = await invoke<boolean>('get_click_through_state');
-            setWasClickThroughEnabled(currentState);
-
-            if (currentState) {
-              await invoke('set_click_through', { enabled: false });
-            }
-          } catch (e) {
-            console.error('Failed to get/set click-through state:', e);
-          }
-
-          setIsSettingsOpen(true);
+        unlistenSettings = await listenBackend('open-settings', () => {
+          if (isMounted) setIsSettingsOpen(true);
         });
 
-        unlistenThemeCustomizer = await listen('open-theme-customizer', async () => {
-          if (!isMounted) return;
-          try {
-            const currentState = await invoke<boolean>('get_click_through_state');
-            setWasClickThroughEnabled(currentState);
-
-            if (currentState) {
-              await invoke('set_click_through', { enabled: false });
-            }
-          } catch (e) {
-            console.error('Failed to get/set click-through state:', e);
-          }
-
-          setIsThemeCustomizerOpen(true);
+        unlistenThemeCustomizer = await listenBackend('open-theme-customizer', () => {
+          if (isMounted) setIsThemeCustomizerOpen(true);
         });
 
-        unlistenColorPresets = await listen('open-color-presets', async () => {
-          if (!isMounted) return;
-          try {
-            const currentState = await invoke<boolean>('get_click_through_state');
-            setWasClickThroughEnabled(currentState);
-
-            if (currentState) {
-              await invoke('set_click_through', { enabled: false });
-            }
-          } catch (e) {
-            console.error('Failed to get/set click-through state:', e);
-          }
-
-          setIsThemeCustomizerOpen(true);
+        unlistenColorPresets = await listenBackend('open-color-presets', () => {
+          if (isMounted) setIsThemeCustomizerOpen(true);
         });
 
-        unlistenWelcome = await listen('open-welcome', async () => {
-          if (!isMounted) return;
-          try {
-            const currentState = await invoke<boolean>('get_click_through_state');
-            setWasClickThroughEnabled(currentState);
+        unlistenWelcome = await listenBackend('open-welcome', () => {
+          if (isMounted) setShowWelcome(true);
+        });
 
-            if (currentState) {
-              await invoke('set_click_through', { enabled: false });
-            }
-          } catch (e) {
-            console.error('Failed to get/set click-through state:', e);
-          }
+        unlistenConfig = await listenBackend('config-changed', ({ config: saved }) => {
+          if (isMounted) applyConfig(saved);
+        });
 
-          setShowWelcome(true);
+        unlistenIntensity = await listenBackend('intensity-changed', ({ intensity: next }) => {
+          if (isMounted) setIntensity(next);
         });
       } catch (e) {
         console.error('Failed to setup listeners:', e);
       }
     };
 
-    // Detect platform
-    platform()
-      .then((platformName) => {
+    // Detect platform and load the saved config
+    Promise.all([platform(), loadConfig(), isConfigNew()])
+      .then(([platformName, savedConfig, isNew]) => {
         if (!isMounted) return;
+        applyConfig(savedConfig);
+
+        // Carry over what an older version kept in localStorage, once
+        const legacyConfig = isNew && !isSecondaryOverlay ? withLegacySettings(savedConfig) : null;
+        if (legacyConfig) {
+          applyConfig(legacyConfig);
+          saveConfig(legacyConfig)
+            .then(clearLegacySettings)
+            .catch((e) => console.error('Failed to import settings:', e));
+        }
+
         const isAndroidPlatform = platformName === 'android';
         setIsAndroid(isAndroidPlatform);
 
@@ -119,54 +131,19 @@ function App(): JSX.Element {
           return;
         }
 
-        const hasSeenWelcome = localStorage.getItem('hasSeenWelcome');
-        if (!hasSeenWelcome) {
+        invoke<number>('get_intensity')
+          .then((current) => {
+            if (isMounted) setIntensity(current);
+          })
+          .catch(console.error);
+
+        if (!appConfigRef.current.hasSeenWelcome && !isSecondaryOverlay) {
           setShowWelcome(true);
-          localStorage.setItem('hasSeenWelcome', 'true');
-          invoke('set_click_through', { enabled: false }).catch(console.error);
+          updateConfig({ hasSeenWelcome: true });
         }
       })
       .catch(console.error);
 
-    // Load saved config
-    const savedConfig = localStorage.getItem('fluidConfig');
-    if (savedConfig) {
-      try {
-        setConfig(JSON.parse(savedConfig));
-      } catch (e) {
-        console.error('Failed to load config:', e);
-      }
-    }
-
-    // Load saved colors
-    const savedHueRange = localStorage.getItem('colorHueRange');
-    const savedSaturation = localStorage.getItem('colorSaturation');
-    const savedBrightness = localStorage.getItem('colorBrightness');
-
-    if (savedHueRange) {
-      try {
-        setColorHueRange(JSON.parse(savedHueRange));
-      } catch (e) {
-        console.error('Failed to load hue range:', e);
-      }
-    }
-
-    if (savedSaturation) {
-      try {
-        setColorSaturation(parseFloat(savedSaturation));
-      } catch (e) {
-        console.error('Failed to load saturation:', e);
-      }
-    }
-
-    if (savedBrightness) {
-      try {
-        setColorBrightness(parseFloat(savedBrightness));
-      } catch (e) {
-        console.error('Failed to load brightness:', e);
-      }
-    }
-
     // Setup listeners
     setupListeners();
 
@@ -186,38 +163,22 @@ function App(): JSX.Element {
       if (unlistenThemeCustomizer) unlistenThemeCustomizer();
       if (unlistenColorPresets) unlistenColorPresets();
       if (unlistenWelcome) unlistenWelcome();
+      if (unlistenConfig) unlistenConfig();
+      if (unlistenIntensity) unlistenIntensity();
       window.removeEventListener('keydown', handleKeyDown);
     };
   }, []);
 
   const handleConfigChange = (newConfig: FluidConfig): void => {
-    setConfig(newConfig);
-    localStorage.setItem('fluidConfig', JSON.stringify(newConfig));
+    updateActiveProfile({ fluid: newConfig });
   };
 
-  const handleSettingsClose = async (): Promise<void> => {
+  const handleSettingsClose = (): void => {
     setIsSettingsOpen(false);
-
-    // Re-enable click-through if it was enabled before opening settings
-    if (wasClickThroughEnabled) {
-      try {
-        await invoke('set_click_through', { enabled: true });
-      } catch (e) {
-        console.error('Failed to restore click-through state:', e);
-      }
-    }
   };
 
-  const handleWelcomeComplete = async (): Promise<void> => {
+  const handleWelcomeComplete = (): void => {
     setShowWelcome(false);
-    // Re-enable click-through if it was enabled before opening welcome
-    if (wasClickThroughEnabled) {
-      try {
-        await invoke('set_click_through', { enabled: true });
-      } catch (e) {
-        console.error('Failed to restore click-through state:', e);
-      }
-    }
   };
 
   const handleAndroidPermissionGranted = (): void => {
@@ -226,28 +187,13 @@ function App(): JSX.Element {
 
   const handleColorChange = useCallback(
     (hueRange: [number, number], saturation: number, brightness: number): void => {
-      setColorHueRange(hueRange);
-      setColorSaturation(saturation);
-      setColorBrightness(brightness);
-
-      // Save to localStorage
-      localStorage.setItem('colorHueRange', JSON.stringify(hueRange));
-      localStorage.setItem('colorSaturation', saturation.toString());
-      localStorage.setItem('colorBrightness', brightness.toString());
+      updateActiveProfile({ palette: { hueRange, saturation, brightness } });
     },
     []
   );
 
-  const handleThemeCustomizerClose = async (): Promise<void> => {
+  const handleThemeCustomizerClose = (): void => {
     setIsThemeCustomizerOpen(false);
-
-    if (wasClickThroughEnabled) {
-      try {
-        await invoke('set_click_through', { enabled: true });
-      } catch (e) {
-        console.error('Failed to restore click-through state:', e);
-      }
-    }
   };
 
   // Android UI
@@ -286,28 +232,43 @@ function App(): JSX.Element {
     );
   }
 
+  const fluid = (
+    <MagicMouse
+      PRESSURE={config.PRESSURE}
+      CURL={config.CURL}
+      SPLAT_RADIUS={config.SPLAT_RADIUS}
+      SPLAT_FORCE={config.SPLAT_FORCE * intensity}
+      DENSITY_DISSIPATION={config.DENSITY_DISSIPATION}
+      VELOCITY_DISSIPATION={config.VELOCITY_DISSIPATION}
+      COLOR_UPDATE_SPEED={config.COLOR_UPDATE_SPEED}
+      SHADING={config.SHADING}
+      colorHueRange={colorHueRange}
+      colorSaturation={colorSaturation}
+      colorBrightness={colorBrightness * intensity}
+      reactToClicks={behavior.reactToClicks}
+      reactToScroll={behavior.reactToScroll}
+    />
+  );
+
+  if (isSecondaryOverlay) {
+    return fluid;
+  }
+
   // Desktop/Main UI
   return (
     <>
       {showWelcome && <Welcome onComplete={handleWelcomeComplete} />}
-      <MagicMouse
-        PRESSURE={config.PRESSURE}
-        CURL={config.CURL}
-        SPLAT_RADIUS={config.SPLAT_RADIUS}
-        SPLAT_FORCE={config.SPLAT_FORCE}
-        DENSITY_DISSIPATION={config.DENSITY_DISSIPATION}
-        VELOCITY_DISSIPATION={config.VELOCITY_DISSIPATION}
-        COLOR_UPDATE_SPEED={config.COLOR_UPDATE_SPEED}
-        SHADING={config.SHADING}
-        colorHueRange={colorHueRange}
-        colorSaturation={colorSaturation}
-        colorBrightness={colorBrightness}
-      />
+      {fluid}
       <Settings
         isOpen={isSettingsOpen}
         onClose={handleSettingsClose}
         config={config}
         onConfigChange={handleConfigChange}
+        profiles={appConfig.profiles.map((profile) => profile.name)}
+        activeProfile={appConfig.activeProfile}
+        hotkeys={appConfig.hotkeys}
+        fullscreenApp={appConfig.fullscreenApp}
+        onFullscreenAppChange={(fullscreenApp) => updateConfig({ fullscreenApp })}
       />
       {isThemeCustomizerOpen && (
         <ThemeCustomizer
@@ -319,6 +280,7 @@ function App(): JSX.Element {
         />
       )}
       {!isAndroid && <ClickThroughIndicator />}
+      {!isAndroid && <ConfigWarning />}
     </>
   );
 }
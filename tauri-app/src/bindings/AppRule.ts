@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { RuleAction } from "./RuleAction";
+
+export type AppRule = { 
+/**
+ * Instance or class name from `WM_CLASS`, e.g. `firefox`; any case
+ */
+wmClass: string | null, 
+/**
+ * Name of the owning process, e.g. `code`
+ */
+process: string | null, 
+/**
+ * Regular expression searched for in the window title
+ */
+title: string | null, action: RuleAction, };
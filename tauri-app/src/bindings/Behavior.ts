@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * How the overlay behaves while a profile is active.
+ */
+export type Behavior = { 
+/**
+ * Click-through state applied when the profile is activated
+ */
+clickThrough: boolean, 
+/**
+ * Splat on global primary clicks
+ */
+reactToClicks: boolean, 
+/**
+ * Push the fluid on global wheel events
+ */
+reactToScroll: boolean, };
@@ -0,0 +1,24 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { AppRule } from "./AppRule";
+import type { FullscreenAction } from "./FullscreenAction";
+import type { Hotkeys } from "./Hotkeys";
+import type { Profile } from "./Profile";
+
+/**
+ * Everything the frontend used to keep in localStorage.
+ *
+ * Missing fields fall back to their defaults, so partial files still load.
+ */
+export type Config = { profiles: Array<Profile>, 
+/**
+ * Name of the profile in use
+ */
+activeProfile: string, hasSeenWelcome: boolean, hotkeys: Hotkeys, 
+/**
+ * What to do while a full-screen application has focus
+ */
+fullscreenApp: FullscreenAction, 
+/**
+ * Applied while the application they name has focus
+ */
+rules: Array<AppRule>, };
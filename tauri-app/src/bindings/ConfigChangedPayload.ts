@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Config } from "./Config";
+
+/**
+ * Payload of `config-changed`.
+ */
+export type ConfigChangedPayload = { version: number, config: Config, };
@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FieldError } from "./FieldError";
+
+/**
+ * Why a config was refused, as the frontend receives it.
+ */
+export type ConfigError = { message: string, 
+/**
+ * The settings out of range; empty when something else is wrong
+ */
+fields: Array<FieldError>, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `config-warning`.
+ */
+export type ConfigWarningPayload = { version: number, 
+/**
+ * Why the config file on disk was not applied
+ */
+message: string, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A numeric setting outside its allowed range.
+ */
+export type FieldError = { 
+/**
+ * Path of the setting as named in the config file, e.g. `fluid.CURL`
+ */
+field: string, value: number, min: number, max: number, };
@@ -0,0 +1,36 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Fluid simulation parameters, named like the `MagicMouse` props.
+ *
+ * `validate` and `clamp` enforce the ranges below.
+ */
+export type FluidConfig = { 
+/**
+ * Pressure solver strength, 0 to 1
+ */
+PRESSURE: number, 
+/**
+ * Vorticity (swirl) strength, 0 to 30
+ */
+CURL: number, 
+/**
+ * Size of the cursor splat, 0.05 to 0.5
+ */
+SPLAT_RADIUS: number, 
+/**
+ * Strength of the cursor splat, 1000 to 20000
+ */
+SPLAT_FORCE: number, 
+/**
+ * How quickly color fades, 0.1 to 10
+ */
+DENSITY_DISSIPATION: number, 
+/**
+ * How quickly motion slows, 0.1 to 10
+ */
+VELOCITY_DISSIPATION: number, 
+/**
+ * How fast the colors cycle, 1 to 50
+ */
+COLOR_UPDATE_SPEED: number, SHADING: boolean, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Shortcut for each action that can be run from the keyboard.
+ */
+export type Hotkeys = { toggleClickThrough: string, 
+/**
+ * Pause or resume following the cursor
+ */
+toggleTracking: string, 
+/**
+ * Switch to the next profile, wrapping around
+ */
+nextProfile: string, 
+/**
+ * Show or hide every overlay
+ */
+toggleVisible: string, };
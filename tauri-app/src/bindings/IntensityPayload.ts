@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `intensity-changed`.
+ */
+export type IntensityPayload = { version: number, 
+/**
+ * Factor for the splat force and brightness of the active profile,
+ * 0 to 1, lowered by per-application rules
+ */
+intensity: number, };
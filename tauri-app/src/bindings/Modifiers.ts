@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Keyboard modifiers held while a button or wheel event happened.
+ */
+export type Modifiers = { shift: boolean, control: boolean, alt: boolean, meta: boolean, };
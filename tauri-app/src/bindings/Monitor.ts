@@ -0,0 +1,26 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * One active display output, in the global desktop coordinate space.
+ */
+export type Monitor = { 
+/**
+ * Output name (e.g. `DP-1`), stable across reconnects
+ */
+id: string, x: number, y: number, 
+/**
+ * Width in physical pixels, after rotation
+ */
+width: number, 
+/**
+ * Height in physical pixels, after rotation
+ */
+height: number, 
+/**
+ * Clockwise rotation in degrees: 0, 90, 180 or 270
+ */
+rotation: number, primary: boolean, 
+/**
+ * Physical pixels per logical (CSS) pixel, as used by the webview
+ */
+scaleFactor: number, };
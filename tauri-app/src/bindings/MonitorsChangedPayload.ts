@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Monitor } from "./Monitor";
+
+/**
+ * Payload of `monitors-changed`.
+ */
+export type MonitorsChangedPayload = { version: number, monitors: Array<Monitor>, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Modifiers } from "./Modifiers";
+
+/**
+ * Payload of `global-mouse-down` and `global-mouse-up`.
+ */
+export type MouseButtonPayload = { version: number, 
+/**
+ * DOM `MouseEvent.button` numbering
+ */
+button: number, modifiers: Modifiers, x: number, y: number, logicalX: number, logicalY: number, 
+/**
+ * Physical pixels per CSS pixel on the overlay's monitor
+ */
+scaleFactor: number, };
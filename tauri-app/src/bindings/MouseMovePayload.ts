@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of `global-mouse-move`.
+ */
+export type MouseMovePayload = { version: number, screenWidth: number, screenHeight: number, logicalWidth: number, logicalHeight: number, x: number, y: number, logicalX: number, logicalY: number, 
+/**
+ * Physical pixels per CSS pixel on the overlay's monitor
+ */
+scaleFactor: number, };
@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Colors of the splats.
+ */
+export type Palette = { 
+/**
+ * Hue range of generated splat colors, each end 0 to 1
+ */
+hueRange: [number, number], 
+/**
+ * 0 to 1
+ */
+saturation: number, 
+/**
+ * 0 to 1
+ */
+brightness: number, };
@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Behavior } from "./Behavior";
+import type { FluidConfig } from "./FluidConfig";
+import type { Palette } from "./Palette";
+
+export type Profile = { 
+/**
+ * Unique, shown in the tray
+ */
+name: string, fluid: FluidConfig, palette: Palette, behavior: Behavior, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * A rectangle in CSS pixels, relative to the top-left corner of the window
+ * (as returned by `getBoundingClientRect`).
+ */
+export type Rect = { x: number, y: number, width: number, height: number, };
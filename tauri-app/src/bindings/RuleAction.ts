@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * What a rule does while its application has focus; undone afterwards.
+ */
+export type RuleAction = { "type": "pause" } | { "type": "switchProfile", profile: string, } | { "type": "lowerIntensity", intensity: number, };
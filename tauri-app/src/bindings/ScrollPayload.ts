@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Modifiers } from "./Modifiers";
+
+/**
+ * Payload of `global-scroll`.
+ */
+export type ScrollPayload = { version: number, 
+/**
+ * Wheel notches, positive to the right
+ */
+deltaX: number, 
+/**
+ * Wheel notches, positive downwards
+ */
+deltaY: number, modifiers: Modifiers, x: number, y: number, logicalX: number, logicalY: number, 
+/**
+ * Physical pixels per CSS pixel on the overlay's monitor
+ */
+scaleFactor: number, };
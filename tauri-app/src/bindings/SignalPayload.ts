@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * Payload of the tray requests that carry no data (`open-settings`,
+ * `open-welcome`, ...).
+ */
+export type SignalPayload = { version: number, };
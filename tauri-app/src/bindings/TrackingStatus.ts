@@ -0,0 +1,9 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrackingState } from "./TrackingState";
+
+/**
+ * Which pointer backend is in use and how well it works.
+ *
+ * Sent with `tracking-status-changed` and returned by `get_tracking_status`.
+ */
+export type TrackingStatus = { backend: string, state: TrackingState, message: string | null, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TrackingState } from "./TrackingState";
+
+/**
+ * Payload of `tracking-status-changed`.
+ */
+export type TrackingStatusPayload = { version: number, backend: string, state: TrackingState, message: string | null, };
@@ -1,6 +1,15 @@
-import { useState, useEffect } from 'react';
+import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/tauri';
 import Settings, { FluidConfig } from './Settings';
+import {
+  Config,
+  ConfigError,
+  DEFAULT_CONFIG,
+  activeProfile,
+  loadConfig,
+  saveConfig,
+  withActiveProfile,
+} from '../config';
 import { ColorPreset, COLOR_PRESETS } from './ThemeCustomizer';
 import './AndroidHome.css';
 
@@ -14,28 +23,21 @@ function AndroidHome({ onPermissionGranted }: AndroidHomeProps): JSX.Element {
   const [showSettings, setShowSettings] = useState(false);
   const [showColorPicker, setShowColorPicker] = useState(false);
   const [selectedPreset, setSelectedPreset] = useState<ColorPreset>(COLOR_PRESETS[0]);
-  const [config, setConfig] = useState<FluidConfig>({
-    PRESSURE: 0.1,
-    CURL: 3,
-    SPLAT_RADIUS: 0.2,
-    SPLAT_FORCE: 6000,
-    DENSITY_DISSIPATION: 3.5,
-    VELOCITY_DISSIPATION: 2,
-    COLOR_UPDATE_SPEED: 10,
-    SHADING: true,
-  });
+  const [appConfig, setAppConfig] = useState<Config>(DEFAULT_CONFIG);
+  const appConfigRef = useRef<Config>(DEFAULT_CONFIG);
+  const config = activeProfile(appConfig).fluid;
+
+  const applyConfig = (next: Config): void => {
+    appConfigRef.current = next;
+    setAppConfig(next);
+  };
 
   useEffect(() => {
     checkOverlayPermission();
     // Load saved settings
-    const savedConfig = localStorage.getItem('fluidConfig');
-    if (savedConfig) {
-      try {
-        setConfig(JSON.parse(savedConfig));
-      } catch (e) {
-        console.error('Failed to load config:', e);
-      }
-    }
+    loadConfig()
+      .then(applyConfig)
+      .catch((e) => console.error('Failed to load config:', e));
     const savedPreset = localStorage.getItem('colorPreset');
     if (savedPreset) {
       try {
@@ -73,9 +75,11 @@ function AndroidHome({ onPermissionGranted }: AndroidHomeProps): JSX.Element {
     onPermissionGranted();
   };
 
+  // Saved from the config loaded at startup, as App does
   const handleConfigChange = (newConfig: FluidConfig): void => {
-    setConfig(newConfig);
-    localStorage.setItem('fluidConfig', JSON.stringify(newConfig));
+    const next = withActiveProfile(appConfigRef.current, { fluid: newConfig });
+    applyConfig(next);
+    saveConfig(next).catch((e: ConfigError) => console.error('Failed to save config:', e.message));
   };
 
   const handlePresetChange = (preset: ColorPreset): void => {
@@ -1,5 +1,5 @@
 import { useEffect, useState, useRef } from 'react';
-import { listen } from '@tauri-apps/api/event';
+import { listenBackend } from '../events';
 import './ClickThroughIndicator.css';
 
 export default function ClickThroughIndicator(): JSX.Element {
@@ -13,10 +13,10 @@ export default function ClickThroughIndicator(): JSX.Element {
 
     const setupListener = async () => {
       try {
-        unlistenFn = await listen<boolean>('click-through-changed', (event) => {
+        unlistenFn = await listenBackend('click-through-changed', ({ enabled }) => {
           if (!isMounted) return;
 
-          setIsClickThrough(event.payload);
+          setIsClickThrough(enabled);
           setShowNotification(true);
 
           // Clear existing timeout
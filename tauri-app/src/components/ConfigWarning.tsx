@@ -0,0 +1,62 @@
+import { useEffect, useState, useRef } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { listenBackend } from '../events';
+import './ClickThroughIndicator.css';
+import './ConfigWarning.css';
+
+// Shows why the config file could not be applied, at startup or after an
+// edit on disk. The overlay is usually click-through, so the message hides
+// itself instead of waiting for a click.
+export default function ConfigWarning(): JSX.Element {
+  const [message, setMessage] = useState<string | null>(null);
+  const timeoutRef = useRef<number | null>(null);
+
+  useEffect(() => {
+    let unlistenFn: (() => void) | null = null;
+    let isMounted = true;
+
+    const show = (warning: string): void => {
+      if (!isMounted) return;
+
+      setMessage(warning);
+      if (timeoutRef.current) clearTimeout(timeoutRef.current);
+      timeoutRef.current = setTimeout(() => {
+        if (isMounted) setMessage(null);
+        timeoutRef.current = null;
+      }, 10000);
+    };
+
+    invoke<string | null>('get_config_warning')
+      .then((warning) => {
+        if (warning) show(warning);
+      })
+      .catch((e) => console.error('Failed to get config warning:', e));
+
+    listenBackend('config-warning', ({ message: warning }) => show(warning))
+      .then((fn) => {
+        if (isMounted) unlistenFn = fn;
+        else fn();
+      })
+      .catch((e) => console.error('Failed to setup listener:', e));
+
+    return () => {
+      isMounted = false;
+      if (timeoutRef.current) clearTimeout(timeoutRef.current);
+      if (unlistenFn) unlistenFn();
+    };
+  }, []);
+
+  if (!message) return <></>;
+
+  return (
+    <div className="config-warning-notification">
+      <div className="notification-content">
+        <div className="notification-icon">⚠️</div>
+        <div className="notification-text">
+          <div className="notification-title">Config File Problem</div>
+          <div className="notification-subtitle">{message}</div>
+        </div>
+      </div>
+    </div>
+  );
+}
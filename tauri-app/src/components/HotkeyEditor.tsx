@@ -0,0 +1,63 @@
+import { useEffect, useState } from 'react';
+import { Hotkeys, setHotkeys } from '../config';
+
+const ACTIONS: { key: keyof Hotkeys; label: string }[] = [
+  { key: 'toggleClickThrough', label: 'Toggle click-through' },
+  { key: 'toggleTracking', label: 'Pause / resume tracking' },
+  { key: 'nextProfile', label: 'Next profile' },
+  { key: 'toggleVisible', label: 'Show / hide overlay' },
+];
+
+interface HotkeyEditorProps {
+  hotkeys: Hotkeys;
+}
+
+// Saved together, so swapping two shortcuts never trips the duplicate check
+export default function HotkeyEditor({ hotkeys }: HotkeyEditorProps): JSX.Element {
+  const [draft, setDraft] = useState<Hotkeys>(hotkeys);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setDraft(hotkeys);
+    setError(null);
+  }, [hotkeys]);
+
+  const changed = ACTIONS.some(({ key }) => draft[key] !== hotkeys[key]);
+
+  const handleSave = (): void => {
+    setHotkeys(draft).catch((e) => {
+      console.error('Failed to save shortcuts:', e);
+      setError(String(e));
+    });
+  };
+
+  return (
+    <div className="presets-section profile-bar hotkey-editor">
+      <h3>Shortcuts</h3>
+      {ACTIONS.map(({ key, label }) => (
+        <label key={key}>
+          <span>{label}</span>
+          <input
+            type="text"
+            placeholder="Off"
+            value={draft[key]}
+            onChange={(e) => setDraft({ ...draft, [key]: e.target.value })}
+          />
+        </label>
+      ))}
+      <p className="hint">
+        Work anywhere, even with click-through on, e.g. Ctrl+Alt+Shift+M. Leave empty to turn
+        one off.
+      </p>
+      <div className="preset-buttons">
+        <button disabled={!changed} onClick={handleSave}>
+          Save Shortcuts
+        </button>
+        <button disabled={!changed} onClick={() => setDraft(hotkeys)}>
+          Revert
+        </button>
+      </div>
+      {error && <p className="profile-error">{error}</p>}
+    </div>
+  );
+}
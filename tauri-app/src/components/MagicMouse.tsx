@@ -24,6 +24,8 @@ interface MagicMouseProps {
   colorHueRange?: [number, number];
   colorSaturation?: number;
   colorBrightness?: number;
+  reactToClicks?: boolean;
+  reactToScroll?: boolean;
 }
 
 interface Pointer {
@@ -72,6 +74,8 @@ function MagicMouse({
   colorHueRange = [0, 1],
   colorSaturation = 1.0,
   colorBrightness = 1.0,
+  reactToClicks = true,
+  reactToScroll = true,
 }: MagicMouseProps): JSX.Element {
   const canvasRef = useRef<HTMLCanvasElement>(null);
 
@@ -1412,30 +1416,52 @@ function MagicMouse({
     canvas.addEventListener('webglcontextlost', onContextLost as EventListener, false);
     canvas.addEventListener('webglcontextrestored', onContextRestored as EventListener, false);
 
-    // Listen for global mouse events from Tauri (when click-through is enabled)
-    let unlistenGlobalMouse: (() => void) | null = null;
-    if (isTauri) {
-      import('@tauri-apps/api/event').then(({ listen }) => {
-        listen<{
-          x: number;
-          y: number;
-          screenWidth: number;
-          screenHeight: number;
-        }>('global-mouse-move', (event) => {
-          const { x, y, screenWidth, screenHeight } = event.payload;
-          // Convert screen coordinates to canvas coordinates
-          const rect = canvas.getBoundingClientRect();
-          const canvasX = (x / screenWidth) * rect.width;
-          const canvasY = (y / screenHeight) * rect.height;
+    // Listen for global mouse events from Tauri (when click-through is enabled).
+    // Positions arrive relative to this window's monitor; the logical values
+    // are CSS pixels, so they map onto the canvas without further scaling.
+    const unlistenGlobal: (() => void)[] = [];
+
+    const toCanvas = (logicalX: number, logicalY: number): [number, number] => [
+      scaleByPixelRatio(logicalX),
+      scaleByPixelRatio(logicalY),
+    ];
 
+    if (isTauri) {
+      import('../events').then(({ listenBackend }) => {
+        listenBackend('global-mouse-move', ({ logicalX, logicalY }) => {
           const pointer = pointers[0];
-          const posX = scaleByPixelRatio(canvasX);
-          const posY = scaleByPixelRatio(canvasY);
+          const [posX, posY] = toCanvas(logicalX, logicalY);
           const { color } = pointer;
           updatePointerMoveData(pointer, posX, posY, color);
-        }).then((fn) => {
-          unlistenGlobalMouse = fn;
-        });
+        }).then((fn) => unlistenGlobal.push(fn));
+
+        listenBackend('global-mouse-down', ({ logicalX, logicalY, button }) => {
+          if (!reactToClicks || button !== 0) return;
+
+          const pointer = pointers[0];
+          const [posX, posY] = toCanvas(logicalX, logicalY);
+          updatePointerDownData(pointer, -1, posX, posY);
+          clickSplat(pointer);
+        }).then((fn) => unlistenGlobal.push(fn));
+
+        listenBackend('global-mouse-up', ({ button }) => {
+          if (!reactToClicks || button !== 0) return;
+          updatePointerUpData(pointers[0]);
+        }).then((fn) => unlistenGlobal.push(fn));
+
+        listenBackend('global-scroll', ({ logicalX, logicalY, deltaX, deltaY }) => {
+          if (!reactToScroll) return;
+
+          const [posX, posY] = toCanvas(logicalX, logicalY);
+          // Push the fluid the way the page scrolls
+          splat(
+            posX / canvas.width,
+            1 - posY / canvas.height,
+            -deltaX * config.SPLAT_FORCE * 0.1,
+            deltaY * config.SPLAT_FORCE * 0.1,
+            generateColor()
+          );
+        }).then((fn) => unlistenGlobal.push(fn));
       });
     }
 
@@ -1453,7 +1479,7 @@ function MagicMouse({
       document.removeEventListener('visibilitychange', onVisibility);
       canvas.removeEventListener('webglcontextlost', onContextLost as EventListener, false);
       canvas.removeEventListener('webglcontextrestored', onContextRestored as EventListener, false);
-      if (unlistenGlobalMouse) unlistenGlobalMouse();
+      unlistenGlobal.forEach((unlisten) => unlisten());
       if (rafId) cancelAnimationFrame(rafId);
     };
   }, [
@@ -1474,6 +1500,8 @@ function MagicMouse({
     colorHueRange,
     colorSaturation,
     colorBrightness,
+    reactToClicks,
+    reactToScroll,
   ]);
 
   return (
@@ -1523,6 +1551,8 @@ export default memo(MagicMouse, (prevProps, nextProps) => {
     prevProps.colorHueRange?.[0] === nextProps.colorHueRange?.[0] &&
     prevProps.colorHueRange?.[1] === nextProps.colorHueRange?.[1] &&
     prevProps.colorSaturation === nextProps.colorSaturation &&
-    prevProps.colorBrightness === nextProps.colorBrightness
+    prevProps.colorBrightness === nextProps.colorBrightness &&
+    prevProps.reactToClicks === nextProps.reactToClicks &&
+    prevProps.reactToScroll === nextProps.reactToScroll
   );
 });
@@ -0,0 +1,99 @@
+import { useEffect, useState } from 'react';
+import { open, save } from '@tauri-apps/api/dialog';
+import {
+  createProfile,
+  deleteProfile,
+  duplicateProfile,
+  exportProfile,
+  importProfile,
+  PROFILE_FILE_EXTENSION,
+  renameProfile,
+  switchProfile,
+} from '../config';
+
+const PROFILE_FILE_FILTERS = [
+  { name: 'Magic Cursor Profile', extensions: [PROFILE_FILE_EXTENSION] },
+];
+
+interface ProfileBarProps {
+  profiles: string[];
+  activeProfile: string;
+}
+
+// The backend applies every change and echoes the new config via config-changed
+export default function ProfileBar({ profiles, activeProfile }: ProfileBarProps): JSX.Element {
+  const [name, setName] = useState('');
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    setError(null);
+  }, [activeProfile, profiles]);
+
+  const run = (action: Promise<void>, clearName = true): void => {
+    action
+      .then(() => {
+        if (clearName) setName('');
+      })
+      .catch((e) => {
+        console.error('Profile change failed:', e);
+        setError(String(e));
+      });
+  };
+
+  const handleImport = async (): Promise<void> => {
+    const path = await open({ filters: PROFILE_FILE_FILTERS });
+    if (typeof path === 'string') await importProfile(path);
+  };
+
+  const handleExport = async (): Promise<void> => {
+    const path = await save({
+      defaultPath: `${activeProfile}.${PROFILE_FILE_EXTENSION}`,
+      filters: PROFILE_FILE_FILTERS,
+    });
+    if (path) await exportProfile(activeProfile, path);
+  };
+
+  const trimmed = name.trim();
+
+  return (
+    <div className="presets-section profile-bar">
+      <h3>Profile</h3>
+      <select value={activeProfile} onChange={(e) => run(switchProfile(e.target.value), false)}>
+        {profiles.map((profile) => (
+          <option key={profile} value={profile}>
+            {profile}
+          </option>
+        ))}
+      </select>
+      <input
+        type="text"
+        placeholder="Profile name"
+        value={name}
+        maxLength={64}
+        onChange={(e) => setName(e.target.value)}
+      />
+      <div className="preset-buttons">
+        <button disabled={!trimmed} onClick={() => run(createProfile(trimmed))}>
+          New
+        </button>
+        <button disabled={!trimmed} onClick={() => run(duplicateProfile(activeProfile, trimmed))}>
+          Duplicate
+        </button>
+        <button disabled={!trimmed} onClick={() => run(renameProfile(activeProfile, trimmed))}>
+          Rename
+        </button>
+        <button
+          disabled={profiles.length < 2}
+          onClick={() => run(deleteProfile(activeProfile), false)}
+        >
+          Delete
+        </button>
+      </div>
+      <div className="preset-buttons profile-file-buttons">
+        <button onClick={() => run(handleImport(), false)}>Import…</button>
+        <button onClick={() => run(handleExport(), false)}>Export…</button>
+      </div>
+      {error && <p className="profile-error">{error}</p>}
+    </div>
+  );
+}
@@ -1,22 +1,25 @@
-import { useEffect, useState } from 'react';
+import { useEffect, useRef, useState } from 'react';
+import type { FluidConfig } from '../bindings/FluidConfig';
+import type { FullscreenAction } from '../bindings/FullscreenAction';
+import type { Hotkeys } from '../bindings/Hotkeys';
+import HotkeyEditor from './HotkeyEditor';
+import ProfileBar from './ProfileBar';
+import { useInteractiveRegion } from '../interactiveRegions';
 import './Settings.css';
 
+export type { FluidConfig };
+
 interface SettingsProps {
   isOpen: boolean;
   onClose: () => void;
   config: FluidConfig;
   onConfigChange: (config: FluidConfig) => void;
-}
-
-export interface FluidConfig {
-  PRESSURE: number;
-  CURL: number;
-  SPLAT_RADIUS: number;
-  SPLAT_FORCE: number;
-  DENSITY_DISSIPATION: number;
-  VELOCITY_DISSIPATION: number;
-  COLOR_UPDATE_SPEED: number;
-  SHADING: boolean;
+  // Profile management is shown only when both are given
+  profiles?: string[];
+  activeProfile?: string;
+  hotkeys?: Hotkeys;
+  fullscreenApp?: FullscreenAction;
+  onFullscreenAppChange?: (action: FullscreenAction) => void;
 }
 
 export default function Settings({
@@ -24,8 +27,15 @@ export default function Settings({
   onClose,
   config,
   onConfigChange,
+  profiles,
+  activeProfile,
+  hotkeys,
+  fullscreenApp,
+  onFullscreenAppChange,
 }: SettingsProps): JSX.Element | null {
   const [localConfig, setLocalConfig] = useState<FluidConfig>(config);
+  const panelRef = useRef<HTMLDivElement>(null);
+  useInteractiveRegion(panelRef, isOpen);
 
   useEffect(() => {
     setLocalConfig(config);
@@ -90,7 +100,7 @@ export default function Settings({
 
   return (
     <div className="settings-overlay" onClick={onClose}>
-      <div className="settings-panel" onClick={(e) => e.stopPropagation()}>
+      <div className="settings-panel" ref={panelRef} onClick={(e) => e.stopPropagation()}>
         <div className="settings-header">
           <h2>Fluid Settings</h2>
           <button className="close-btn" onClick={onClose}>
@@ -99,6 +109,10 @@ export default function Settings({
         </div>
 
         <div className="settings-content">
+          {profiles && activeProfile !== undefined && (
+            <ProfileBar profiles={profiles} activeProfile={activeProfile} />
+          )}
+
           <div className="presets-section">
             <h3>Presets</h3>
             <div className="preset-buttons">
@@ -223,6 +237,22 @@ export default function Settings({
               <span>Enable Shading</span>
             </label>
           </div>
+
+          {hotkeys && <HotkeyEditor hotkeys={hotkeys} />}
+
+          {fullscreenApp && onFullscreenAppChange && (
+            <div className="presets-section profile-bar">
+              <h3>Full-Screen Apps</h3>
+              <select
+                value={fullscreenApp}
+                onChange={(e) => onFullscreenAppChange(e.target.value as FullscreenAction)}
+              >
+                <option value="hide">Hide the overlay</option>
+                <option value="pause">Pause the effect</option>
+                <option value="ignore">Keep showing</option>
+              </select>
+            </div>
+          )}
         </div>
 
         <div className="settings-footer">
@@ -1,4 +1,5 @@
 import { useState, useEffect, useRef, useCallback, useMemo } from 'react';
+import { useInteractiveRegion } from '../interactiveRegions';
 import './ThemeCustomizer.css';
 
 export interface HSLColor {
@@ -115,6 +116,9 @@ export default function ThemeCustomizer({
     initialBrightness !== undefined ? initialBrightness : 0.5
   );
 
+  const panelRef = useRef<HTMLDivElement>(null);
+  useInteractiveRegion(panelRef);
+
   const wheelRefs = [
     useRef<HTMLCanvasElement>(null),
     useRef<HTMLCanvasElement>(null),
@@ -396,6 +400,7 @@ export default function ThemeCustomizer({
     >
       <div
         className="theme-customizer-panel"
+        ref={panelRef}
         onClick={(e) => e.stopPropagation()}
         onKeyDown={(e) => e.stopPropagation()}
         role="dialog"
@@ -0,0 +1,166 @@
+import { invoke } from '@tauri-apps/api/tauri';
+import type { AppRule } from './bindings/AppRule';
+import type { Config } from './bindings/Config';
+import type { ConfigError } from './bindings/ConfigError';
+import type { FullscreenAction } from './bindings/FullscreenAction';
+import type { Hotkeys } from './bindings/Hotkeys';
+import type { Profile } from './bindings/Profile';
+
+export type { AppRule, Config, ConfigError, FullscreenAction, Hotkeys, Profile };
+
+// Rendered until the backend has answered `get_config`; mirrors
+// `Profile::default()` in src-tauri/src/config/profiles.rs
+export const DEFAULT_PROFILE: Profile = {
+  name: 'Default',
+  fluid: {
+    PRESSURE: 1,
+    CURL: 4,
+    SPLAT_RADIUS: 0.2,
+    SPLAT_FORCE: 6000,
+    DENSITY_DISSIPATION: 3.5,
+    VELOCITY_DISSIPATION: 2,
+    COLOR_UPDATE_SPEED: 10,
+    SHADING: false,
+  },
+  palette: {
+    hueRange: [0, 1],
+    saturation: 1.0,
+    brightness: 1.0,
+  },
+  behavior: {
+    clickThrough: true,
+    reactToClicks: true,
+    reactToScroll: true,
+  },
+};
+
+// Mirrors `Hotkeys::default()` in src-tauri/src/config/hotkeys.rs
+export const DEFAULT_HOTKEYS: Hotkeys = {
+  toggleClickThrough: 'Ctrl+Alt+Shift+M',
+  toggleTracking: 'Ctrl+Alt+Shift+P',
+  nextProfile: 'Ctrl+Alt+Shift+N',
+  toggleVisible: 'Ctrl+Alt+Shift+H',
+};
+
+export const DEFAULT_CONFIG: Config = {
+  profiles: [DEFAULT_PROFILE],
+  activeProfile: DEFAULT_PROFILE.name,
+  hasSeenWelcome: false,
+  hotkeys: DEFAULT_HOTKEYS,
+  fullscreenApp: 'hide',
+  rules: [],
+};
+
+export function activeProfile(config: Config): Profile {
+  return (
+    config.profiles.find((profile) => profile.name === config.activeProfile) ??
+    config.profiles[0] ??
+    DEFAULT_PROFILE
+  );
+}
+
+// Copy of `config` with the active profile changed
+export function withActiveProfile(config: Config, changes: Partial<Profile>): Config {
+  const active = activeProfile(config);
+  return {
+    ...config,
+    profiles: config.profiles.map((profile) =>
+      profile.name === active.name ? { ...profile, ...changes } : profile
+    ),
+  };
+}
+
+export function loadConfig(): Promise<Config> {
+  return invoke<Config>('get_config');
+}
+
+// Rejects with a ConfigError listing every out-of-range setting
+export function saveConfig(config: Config): Promise<void> {
+  return invoke('set_config', { config });
+}
+
+// True when the backend started without a config file
+export function isConfigNew(): Promise<boolean> {
+  return invoke<boolean>('is_config_new');
+}
+
+// Where versions before the config file kept the settings
+const LEGACY_KEYS = [
+  'fluidConfig',
+  'colorHueRange',
+  'colorSaturation',
+  'colorBrightness',
+  'hasSeenWelcome',
+];
+
+function readLegacy<T>(key: string): T | undefined {
+  const value = localStorage.getItem(key);
+  if (value === null) return undefined;
+  try {
+    return JSON.parse(value) as T;
+  } catch (e) {
+    console.error(`Failed to read ${key}:`, e);
+    return undefined;
+  }
+}
+
+// `config` with the settings left in localStorage by an older version
+// applied to the active profile, or null when there are none
+export function withLegacySettings(config: Config): Config | null {
+  if (LEGACY_KEYS.every((key) => localStorage.getItem(key) === null)) return null;
+
+  const { fluid, palette } = activeProfile(config);
+  return {
+    ...withActiveProfile(config, {
+      fluid: { ...fluid, ...readLegacy<Partial<Profile['fluid']>>('fluidConfig') },
+      palette: {
+        hueRange: readLegacy<[number, number]>('colorHueRange') ?? palette.hueRange,
+        saturation: readLegacy<number>('colorSaturation') ?? palette.saturation,
+        brightness: readLegacy<number>('colorBrightness') ?? palette.brightness,
+      },
+    }),
+    hasSeenWelcome: config.hasSeenWelcome || readLegacy<boolean>('hasSeenWelcome') === true,
+  };
+}
+
+export function clearLegacySettings(): void {
+  LEGACY_KEYS.forEach((key) => localStorage.removeItem(key));
+}
+
+// Profile management; the backend answers every change with config-changed
+
+export function createProfile(name: string): Promise<void> {
+  return invoke('create_profile', { name });
+}
+
+export function duplicateProfile(name: string, newName: string): Promise<void> {
+  return invoke('duplicate_profile', { name, newName });
+}
+
+export function renameProfile(name: string, newName: string): Promise<void> {
+  return invoke('rename_profile', { name, newName });
+}
+
+export function deleteProfile(name: string): Promise<void> {
+  return invoke('delete_profile', { name });
+}
+
+export function switchProfile(name: string): Promise<void> {
+  return invoke('switch_profile', { name });
+}
+
+// Shareable `.magiccursor` files; importing also switches to the new profile
+export const PROFILE_FILE_EXTENSION = 'magiccursor';
+
+export function exportProfile(name: string, path: string): Promise<string> {
+  return invoke<string>('export_profile', { name, path });
+}
+
+export function importProfile(path: string): Promise<string> {
+  return invoke<string>('import_profile', { path });
+}
+
+// Global shortcuts; rejected when one can't be parsed or two are the same
+export function setHotkeys(hotkeys: Hotkeys): Promise<void> {
+  return invoke('set_hotkeys', { hotkeys });
+}
@@ -0,0 +1,50 @@
+import { listen, UnlistenFn } from '@tauri-apps/api/event';
+import type { ClickThroughPayload } from './bindings/ClickThroughPayload';
+import type { ConfigChangedPayload } from './bindings/ConfigChangedPayload';
+import type { ConfigWarningPayload } from './bindings/ConfigWarningPayload';
+import type { IntensityPayload } from './bindings/IntensityPayload';
+import type { MonitorsChangedPayload } from './bindings/MonitorsChangedPayload';
+import type { MouseButtonPayload } from './bindings/MouseButtonPayload';
+import type { MouseMovePayload } from './bindings/MouseMovePayload';
+import type { ScrollPayload } from './bindings/ScrollPayload';
+import type { SignalPayload } from './bindings/SignalPayload';
+import type { TrackingStatusPayload } from './bindings/TrackingStatusPayload';
+
+// Must match `SCHEMA_VERSION` in src-tauri/src/events.rs
+export const SCHEMA_VERSION = 1;
+
+// Payload type of every event the backend emits, keyed by event name
+export interface BackendEvents {
+  'global-mouse-move': MouseMovePayload;
+  'global-mouse-down': MouseButtonPayload;
+  'global-mouse-up': MouseButtonPayload;
+  'global-scroll': ScrollPayload;
+  'click-through-changed': ClickThroughPayload;
+  'tracking-status-changed': TrackingStatusPayload;
+  'monitors-changed': MonitorsChangedPayload;
+  'config-changed': ConfigChangedPayload;
+  'config-warning': ConfigWarningPayload;
+  'intensity-changed': IntensityPayload;
+  'open-color-presets': SignalPayload;
+  'open-theme-customizer': SignalPayload;
+  'open-settings': SignalPayload;
+  'open-welcome': SignalPayload;
+  'request-overlay-permission': SignalPayload;
+}
+
+/**
+ * Typed `listen` for backend events. Payloads from a different schema
+ * version are dropped with a warning instead of being misread.
+ */
+export function listenBackend<K extends keyof BackendEvents>(
+  event: K,
+  handler: (payload: BackendEvents[K]) => void
+): Promise<UnlistenFn> {
+  return listen<BackendEvents[K]>(event, ({ payload }) => {
+    if (payload.version !== SCHEMA_VERSION) {
+      console.warn(`Ignoring ${event} with schema version ${payload.version}`);
+      return;
+    }
+    handler(payload);
+  });
+}
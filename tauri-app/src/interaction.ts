@@ -0,0 +1,39 @@
+import { useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+
+// Leases keep click-through off while held. The backend counts them and
+// restores the user's choice after the last release, or when the page
+// that took them reloads.
+
+export function acquireInteraction(): Promise<number> {
+  return invoke<number>('acquire_interaction');
+}
+
+export function releaseInteraction(id: number): Promise<void> {
+  return invoke('release_interaction', { id });
+}
+
+/** Holds a lease while `active`, e.g. while a panel is open. */
+export function useInteractionLease(active: boolean): void {
+  useEffect(() => {
+    if (!active) return;
+
+    let lease: number | null = null;
+    let released = false;
+    const release = (id: number): void => {
+      releaseInteraction(id).catch((e) => console.error('Failed to release interaction:', e));
+    };
+
+    acquireInteraction()
+      .then((id) => {
+        if (released) release(id);
+        else lease = id;
+      })
+      .catch((e) => console.error('Failed to acquire interaction:', e));
+
+    return () => {
+      released = true;
+      if (lease !== null) release(lease);
+    };
+  }, [active]);
+}
@@ -0,0 +1,66 @@
+import { RefObject, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/tauri';
+import { appWindow } from '@tauri-apps/api/window';
+import type { Rect } from './bindings/Rect';
+
+export type { Rect };
+
+// Elements that keep taking clicks while click-through is on (Linux only;
+// elsewhere click-through covers the whole window)
+const elements = new Set<Element>();
+let reportQueued = false;
+
+export function setInteractiveRegions(regions: Rect[]): Promise<void> {
+  return invoke('set_interactive_regions', { regions });
+}
+
+function report(): void {
+  reportQueued = false;
+  const regions = [...elements].map((element) => {
+    const { x, y, width, height } = element.getBoundingClientRect();
+    return { x, y, width, height };
+  });
+  setInteractiveRegions(regions).catch((e) =>
+    console.error('Failed to set interactive regions:', e)
+  );
+}
+
+// Changes made in the same frame are sent together
+function queueReport(): void {
+  if (reportQueued) return;
+  reportQueued = true;
+  requestAnimationFrame(report);
+}
+
+const resizeObserver = new ResizeObserver(queueReport);
+window.addEventListener('resize', queueReport);
+
+// Only the main window takes input; a reloaded page drops the regions the
+// previous one left behind
+if (appWindow.label === 'main') {
+  setInteractiveRegions([]).catch(console.error);
+}
+
+/**
+ * Keeps the element in `ref` clickable while `active`, even with
+ * click-through on. Follows its size and entrance animation, but not
+ * other moves.
+ */
+export function useInteractiveRegion(ref: RefObject<Element>, active = true): void {
+  useEffect(() => {
+    const element = ref.current;
+    if (!active || !element) return;
+
+    elements.add(element);
+    resizeObserver.observe(element);
+    // Entrance animations move the element without resizing it
+    element.addEventListener('animationend', queueReport);
+    queueReport();
+    return () => {
+      elements.delete(element);
+      resizeObserver.unobserve(element);
+      element.removeEventListener('animationend', queueReport);
+      queueReport();
+    };
+  }, [ref, active]);
+}